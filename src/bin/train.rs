@@ -0,0 +1,378 @@
+use game_quad::cli::Args;
+use game_quad::game::renderer::PongDrawer;
+use macroquad::prelude::*;
+
+use std::{error::Error, time::Duration, time::Instant};
+
+use game_quad::neural_network::network_drawer::NetworkDrawer;
+use game_quad::neural_network::neural_network_f::NeuralNetwork;
+use game_quad::game::recording::load_frames;
+use game_quad::training::checkpoint::Checkpoint;
+use game_quad::training::cloning::{clone_behavior, CloningConfig};
+use game_quad::training::config::TrainingConfig;
+use game_quad::training::rl::{train_policy, RlConfig};
+use game_quad::training::population::{
+    generation_finished, update_all_games, GamePack, GenerationStats, TrainingState,
+};
+
+fn main() {
+    let args = Args::from_env();
+
+    // `train convert <entrada> <saída>` converte uma rede entre binário e JSON
+    if args.positional.first().map(String::as_str) == Some("convert") {
+        if let Err(e) = convert_network(&args.positional[1..]) {
+            eprintln!("Não foi possível converter a rede: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // `train clone <gravação> <saída>` treina uma rede para imitar uma partida gravada
+    if args.positional.first().map(String::as_str) == Some("clone") {
+        if let Err(e) = clone_recording(&args) {
+            eprintln!("Não foi possível treinar a partir da gravação: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // `train rl <saída>` treina uma rede por reforço, jogando contra o bot
+    if args.positional.first().map(String::as_str) == Some("rl") {
+        if let Err(e) = train_reinforcement(&args) {
+            eprintln!("Não foi possível treinar por reforço: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let (config, state) = match start_training(&args) {
+        Ok(training) => training,
+        Err(e) => {
+            eprintln!("Não foi possível iniciar o treino: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // `--headless` treina sem abrir janela, sem esperar pelo vsync
+    if args.flag("headless") {
+        run_headless(&config, state);
+    } else {
+        macroquad::Window::from_config(window_conf(), run_windowed(config, state));
+    }
+}
+
+/// Converte a rede `paths[0]` para `paths[1]`; o formato de cada lado vem da extensão.
+fn convert_network(paths: &[String]) -> Result<(), Box<dyn Error>> {
+    let [input, output] = paths else {
+        return Err("uso: train convert <entrada> <saída>  (.json para JSON, outra extensão para binário)".into());
+    };
+    let nn = NeuralNetwork::load_neural_network(input)?;
+    nn.save_neural_network(output)?;
+    println!("{} -> {}", input, output);
+    Ok(())
+}
+
+/// Treina por retropropagação uma rede que imita a gravação `positional[1]` e a salva em `positional[2]`.
+fn clone_recording(args: &Args) -> Result<(), Box<dyn Error>> {
+    let [_, recording, output] = args.positional.as_slice() else {
+        return Err("uso: train clone <gravação.jsonl> <saída> [--epochs N] [--optimizer adam|sgd] ...".into());
+    };
+    let config = CloningConfig::from_args(args)?;
+    let frames = load_frames(recording)?;
+    println!("{} passos gravados em {}", frames.len(), recording);
+
+    let nn = clone_behavior(&frames, &config, |epoch, loss| {
+        println!("Época {}: perda {:.5}", epoch, loss);
+    })?;
+    nn.save_neural_network(output)?;
+    println!("Rede salva em {}", output);
+    Ok(())
+}
+
+/// Treina uma política por reforço (REINFORCE ou DQN) e a salva em `positional[1]`.
+fn train_reinforcement(args: &Args) -> Result<(), Box<dyn Error>> {
+    let [_, output] = args.positional.as_slice() else {
+        return Err("uso: train rl <saída> [--algorithm reinforce|dqn] [--episodes N] [--gamma G] ...".into());
+    };
+    let config = RlConfig::from_args(args)?;
+    let nn = train_policy(&config, |stats| {
+        println!(
+            "Episódio {}: recompensa média {:.3}, rebatidas médias {:.2}",
+            stats.episodes, stats.average_reward, stats.average_hits
+        );
+    })?;
+    nn.save_neural_network(output)?;
+    println!("Rede salva em {}", output);
+    Ok(())
+}
+
+/// Monta o treino: retoma de `--resume <checkpoint>` (aplicando as flags por cima da
+/// configuração salva) ou começa do zero com a configuração da linha de comando.
+fn start_training(args: &Args) -> Result<(TrainingConfig, TrainingState), Box<dyn Error>> {
+    if let Some(path) = args.value("resume") {
+        let (mut config, state) = Checkpoint::load(path)?.into_state();
+        config.apply_args(args)?;
+        config.validate()?;
+        if config.games != state.games.len() {
+            return Err(format!(
+                "o checkpoint tem {} jogos, mas a configuração pede {}",
+                state.games.len(),
+                config.games
+            )
+            .into());
+        }
+        println!("Retomando {} após a geração {}", path, state.generation);
+        return Ok((config, state));
+    }
+
+    let config = TrainingConfig::from_args(args)?;
+    let seed = config.seed.unwrap_or_else(::rand::random);
+    println!("Semente do treino: {}", seed);
+    let state = TrainingState::new(&config, seed)?;
+    Ok((config, state))
+}
+
+/// Salva o checkpoint se estiver configurado e for a hora.
+fn save_checkpoint_if_due(config: &TrainingConfig, state: &TrainingState) {
+    let Some(path) = &config.checkpoint else {
+        return;
+    };
+    if !state.generation.is_multiple_of(config.checkpoint_every) {
+        return;
+    }
+    match Checkpoint::capture(config, state).save(path) {
+        Ok(()) => println!("Checkpoint salvo em {}", path),
+        Err(e) => eprintln!("Falha ao salvar o checkpoint {}: {}", path, e),
+    }
+}
+
+/// Imprime uma linha com o resumo da geração.
+fn print_stats(stats: &GenerationStats, elapsed: Duration) {
+    let species = match stats.species {
+        0 => String::new(),
+        n => format!("  espécies {}", n),
+    };
+    // A variância só existe com mais de um episódio por rede
+    let variance = match stats.variance > 0.0 {
+        true => format!("  variância {:.2} (melhor {:.2})", stats.variance, stats.best_variance),
+        false => String::new(),
+    };
+    let champions = match stats.champions {
+        0 => String::new(),
+        n => format!("  campeões {}", n),
+    };
+    println!(
+        "Geração {}: média {:.2}  melhor {:.2}  pior {:.2}{}{}{}  ({:.2?})",
+        stats.generation, stats.average, stats.best, stats.worst, variance, species, champions, elapsed
+    );
+}
+
+/// Treino sem janela: roda gerações inteiras o mais rápido que a CPU permitir
+/// até atingir `max_generations` ou `target_fitness`.
+fn run_headless(config: &TrainingConfig, mut state: TrainingState) {
+    loop {
+        let started = Instant::now();
+        let stats = state.run_generation_headless(config);
+        print_stats(&stats, started.elapsed());
+        save_checkpoint_if_due(config, &state);
+        if config.should_stop(stats.generation, stats.best) {
+            break;
+        }
+    }
+}
+
+/// Treino com janela: avança um passo de cada jogo por quadro e desenha a população.
+async fn run_windowed(config: TrainingConfig, mut state: TrainingState) {
+    let mut generation_started = Instant::now();
+    
+    // Cria a UI para desenhar (apenas a rede neural do melhor jogo)
+    let mut network_drawer = NetworkDrawer::new(
+        vec2(50.0, 50.0),
+        15.0,
+        50.0,
+        30.0,
+        Color::from_rgba(3, 223, 252, 255),
+    );
+    let pong_drawer = PongDrawer::default();
+
+    
+    // Variáveis da câmera
+    let mut scale = 1.0;
+    let mut camera_pos = vec2(0.0, 0.0);
+    let camera_speed = 5.0;
+
+    // Índice do melhor jogo (aquele com a maior pontuação)
+    let mut best_game_index: usize = 0;
+
+    loop {
+        // 1) Ajusta o zoom com a roda do mouse
+        let scroll = mouse_wheel();
+        if scroll.1 > 0.0 {
+            scale += 0.1;
+        } else if scroll.1 < 0.0 {
+            scale -= 0.1;
+            if scale < 0.1 {
+                scale = 0.1;
+            }
+        }
+
+        // 2) Move a câmera com WASD
+        if is_key_down(KeyCode::W) {
+            camera_pos.y -= camera_speed;
+        }
+        if is_key_down(KeyCode::S) {
+            camera_pos.y += camera_speed;
+        }
+        if is_key_down(KeyCode::A) {
+            camera_pos.x -= camera_speed;
+        }
+        if is_key_down(KeyCode::D) {
+            camera_pos.x += camera_speed;
+        }
+
+        // 3) Se apertar Space, finaliza todos os jogos
+        if is_key_pressed(KeyCode::Space) {
+            for game in state.games.iter_mut() {
+                if !game.finished {
+                    game.finished = true;
+                }
+            }
+        }
+
+        // 4) Ajusta a câmera
+        let camera = Camera2D {
+            zoom: vec2(scale * 2.0 / screen_width(), scale * 2.0 / screen_height()),
+            target: camera_pos,
+            ..Default::default()
+        };
+        set_camera(&camera);
+
+        // 5) Atualiza e desenha todos os jogos
+        clear_background(WHITE);
+
+        // Atualiza os jogos e determina quantos já terminaram
+        let finished_count =
+            update_all_games(&mut state.games, &mut best_game_index, config.max_steps);
+        draw_all_games(&state.games, &pong_drawer, config.games_drawn);
+
+        // Se quase todos os jogos terminaram, gera uma nova geração
+        if generation_finished(finished_count, &config) {
+            let stats = state.finish_generation(&config);
+            print_stats(&stats, generation_started.elapsed());
+            save_checkpoint_if_due(&config, &state);
+            generation_started = Instant::now();
+            if config.should_stop(stats.generation, stats.best) {
+                break;
+            }
+        }
+
+       
+        // 6) Desenha a rede neural do melhor jogo no canto superior esquerdo
+        set_default_camera();
+     
+        network_drawer.draw(&mut state.games[best_game_index].neural_network.lock().unwrap());
+        draw_text(
+            format!(
+                "Pontuação: {}  {}/{} jogadores :D Geração {}",
+                state.games[best_game_index].pontuation,
+                finished_count,
+                config.games,
+                state.generation
+            )
+            .as_str(),
+            600.0,
+            50.0,
+            40.0,
+            BLACK,
+        );
+        //use relative position of the screen
+        let x = screen_width() - 330.0;
+        draw_graphic(
+            &state.history.iter().map(|stats| vec2(stats.generation as f32, stats.average as f32)).collect::<Vec<_>>(),
+            vec2(x, 110.0), // Posição onde o gráfico será desenhado
+            vec2(240.0, 160.0), // Tamanho da área do gráfico
+        );
+        next_frame().await;
+    }
+}
+
+/// Função que retorna a configuração da janela.
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Pong AI com Scroll Zoom e WASD".to_owned(),
+        window_width: 800,
+        window_height: 600,
+        high_dpi: false,
+        fullscreen: false,
+        ..Default::default()
+    }
+}
+
+/// Desenha todos os jogos ativos (não limpa o fundo a cada desenho).
+fn draw_all_games(games: &[GamePack], pong_drawer: &PongDrawer, games_drawn: usize) {
+    let mut drawn = 0;
+    for game in games.iter() {
+        if game.finished {
+            continue;
+        }
+        pong_drawer.draw(&game.pong);
+        drawn += 1;
+        if drawn >= games_drawn {
+            break;
+        }
+    }
+}
+
+/// Desenha um gráfico a partir dos dados, conectando os pontos com linhas,
+/// e também desenha o contorno (quadrado) da área do gráfico.
+///
+/// - `data_points`: Vetor de pontos (x, y) representando os dados (ex.: média das pontuações por geração).
+/// - `graph_origin`: Posição (x, y) do canto superior esquerdo onde o gráfico será desenhado.
+/// - `graph_size`: Tamanho (largura, altura) da área disponível para o gráfico.
+pub fn draw_graphic(data_points: &[Vec2], graph_origin: Vec2, graph_size: Vec2) {
+    if data_points.is_empty() {
+        return;
+    }
+    let title = "Média de Pontuação por Geração";
+
+    draw_text(title, graph_origin.x - 10.0, graph_origin.y - 10.0, 25.0, BLACK);
+
+    // Calcula os limites dos dados.
+    let data_min_x = data_points.iter().map(|p| p.x).fold(f32::INFINITY, f32::min);
+    let data_max_x = data_points.iter().map(|p| p.x).fold(f32::NEG_INFINITY, f32::max);
+    let data_min_y = data_points.iter().map(|p| p.y).fold(f32::INFINITY, f32::min);
+    let data_max_y = data_points.iter().map(|p| p.y).fold(f32::NEG_INFINITY, f32::max);
+
+    // Determina o intervalo dos dados; evita divisão por zero.
+    let data_range_x = if data_max_x - data_min_x == 0.0 { 1.0 } else { data_max_x - data_min_x };
+    let data_range_y = if data_max_y - data_min_y == 0.0 { 1.0 } else { data_max_y - data_min_y };
+
+    // Calcula os fatores de escala para mapear os dados à área do gráfico.
+    let scale_x = graph_size.x / data_range_x;
+    let scale_y = graph_size.y / data_range_y;
+
+    // Converte os pontos dos dados para as coordenadas do gráfico.
+    // O eixo Y é invertido para que valores maiores fiquem mais acima na área do gráfico.
+    let transformed_points: Vec<Vec2> = data_points.iter().map(|p| {
+        let x = graph_origin.x + (p.x - data_min_x) * scale_x;
+        let y = graph_origin.y + graph_size.y - (p.y - data_min_y) * scale_y;
+        Vec2::new(x, y)
+    }).collect();
+
+    // Desenha o contorno (quadrado) da área do gráfico.
+    draw_rectangle_lines(
+        graph_origin.x,
+        graph_origin.y,
+        graph_size.x,
+        graph_size.y,
+        2.0,  // espessura da linha
+        BLACK,
+    );
+
+    // Desenha linhas conectando os pontos transformados.
+    for i in 0..transformed_points.len() - 1 {
+        let start = transformed_points[i];
+        let end = transformed_points[i + 1];
+        draw_line(start.x, start.y, end.x, end.y, 2.0, RED);
+    }
+}
\ No newline at end of file
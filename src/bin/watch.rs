@@ -0,0 +1,215 @@
+use game_quad::cli::Args;
+use game_quad::game::{
+    controller::{ControlKeys, Controller, HumanController, Latency, MirroredController},
+    pong::{GameWindow, MatchRule, Pong},
+    recording::{append_frames, RecordingController, SharedRecording},
+    renderer::PongDrawer,
+};
+use game_quad::neural_network::{network_drawer::NetworkDrawer, neural_network_f::NeuralNetwork};
+use macroquad::prelude::*;
+use std::sync::{Arc, Mutex};
+
+/// Tamanho e posição do campo na tela.
+const FIELD_WIDTH: f32 = 500.0;
+const FIELD_HEIGHT: f32 = 400.0;
+const FIELD_POSITION: Vec2 = Vec2::new(100.0, 100.0);
+/// Passos acumulados na gravação antes de irem para o arquivo (uns 10 segundos de jogo),
+/// para que uma partida longa não se perca inteira se o programa cair.
+const RECORD_FLUSH_FRAMES: usize = 600;
+
+/// Controlador do jogador 1: a rede (com `latency`), ou o humano (setas) sendo gravado em `--record`.
+fn player1_controller(
+    nn: &Arc<Mutex<NeuralNetwork>>,
+    recording: Option<&SharedRecording>,
+    latency: Latency,
+) -> Box<dyn Controller> {
+    match recording {
+        Some(frames) => Box::new(RecordingController::new(
+            HumanController::new(ControlKeys::ArrowKeys),
+            frames.clone(),
+        )),
+        None => latency.wrap(nn.clone()),
+    }
+}
+
+/// Controlador do jogador 2: o campeão, treinado como jogador 1, jogando com o campo espelhado.
+fn player2_controller(champion: &Arc<Mutex<NeuralNetwork>>, latency: Latency) -> Box<dyn Controller> {
+    let center_x = FIELD_POSITION.x + FIELD_WIDTH / 2.0;
+    latency.wrap(MirroredController::new(champion.clone(), center_x))
+}
+
+/// Latência das redes: `--reaction-ms N` atrasa o que elas enxergam e `--decision-ms M`
+/// faz com que só decidam a cada M milissegundos.
+fn latency_from_args(args: &Args) -> Result<Latency, String> {
+    let delay_ms = args.parse_value::<f32>("reaction-ms")?.unwrap_or(0.0);
+    let interval_ms = args.parse_value::<f32>("decision-ms")?.unwrap_or(0.0);
+    let latency = Latency::new(delay_ms / 1000.0, interval_ms / 1000.0);
+    latency.validate()?;
+    Ok(latency)
+}
+
+/// Salva no arquivo de gravação os passos ainda não gravados.
+fn flush_recording(path: &str, frames: &SharedRecording) {
+    let mut frames = frames.lock().unwrap();
+    if frames.is_empty() {
+        return;
+    }
+    match append_frames(path, &frames) {
+        Ok(()) => println!("{} passos gravados em {}", frames.len(), path),
+        Err(e) => eprintln!("Falha ao gravar {}: {}", path, e),
+    }
+    frames.clear();
+}
+
+#[macroquad::main("Pong AI")]
+async fn main() {
+    let args = Args::from_env();
+    // `--record dados.jsonl`: o jogador 1 passa a ser você (setas) e cada passo é gravado
+    let record_path = args.value("record").map(str::to_owned);
+    let recording: Option<SharedRecording> = record_path.as_ref().map(|_| Arc::default());
+    if recording.is_some() {
+        // Fechar a janela só pede para sair: o loop grava o que falta antes de terminar
+        prevent_quit();
+    }
+
+    let mut drawer = NetworkDrawer::new(
+        vec2(0.0, 50.0),
+        15.0,
+        50.0,
+        30.0,
+        Color::from_rgba(3, 223, 252, 255),
+    );
+
+    let mut drawer2 = NetworkDrawer::new(
+        vec2(600.0, 50.0),
+        15.0,
+        50.0,
+        30.0,
+        Color::from_rgba(250, 60, 60, 255),
+    );
+    let pong_drawer = PongDrawer::default();
+
+    let nn = Arc::new(Mutex::new(
+        NeuralNetwork::load_neural_network("best_nn.bin").unwrap(),
+    ));
+    let my_champion = NeuralNetwork::load_neural_network("best_nn.bin").unwrap();
+    let my_champion = Arc::new(Mutex::new(my_champion));   
+
+    // Semente da partida: `--seed N` reproduz exatamente a partida N
+    let mut match_seed: u64 = match args.parse_value::<u64>("seed") {
+        Ok(seed) => seed.unwrap_or_else(::rand::random),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    // Regra das partidas: `--rule first-to:N` ou `--rule timed:SEGUNDOS`
+    let rule: MatchRule = match args.parse_value("rule") {
+        Ok(rule) => rule.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let latency = match latency_from_args(&args) {
+        Ok(latency) => latency,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Partida com semente {}", match_seed);
+    let mut game = Pong::with_seed(
+        GameWindow::new(FIELD_WIDTH, FIELD_HEIGHT),
+        player1_controller(&nn, recording.as_ref(), latency),
+        player2_controller(&my_champion, latency),
+        FIELD_POSITION,
+        match_seed,
+    );
+    game.rule = rule;
+
+    // Variáveis de câmera
+    let mut scale = 1.0;
+    let mut camera_pos = vec2(0.0, 0.0);
+    let camera_speed = 5.0;
+
+
+    loop {
+        if is_quit_requested() {
+            if let (Some(path), Some(frames)) = (&record_path, &recording) {
+                flush_recording(path, frames);
+            }
+            break;
+        }
+
+        let scroll = mouse_wheel();
+        if scroll.1 > 0.0 {
+            scale += 0.1;
+        } else if scroll.1 < 0.0 {
+            scale -= 0.1;
+            if scale < 0.1 {
+                scale = 0.1;
+            }
+        }
+
+        // 2) Move a câmera com WASD
+        if is_key_down(KeyCode::W) {
+            camera_pos.y -= camera_speed;
+        }
+        if is_key_down(KeyCode::S) {
+            camera_pos.y += camera_speed;
+        }
+        if is_key_down(KeyCode::A) {
+            camera_pos.x -= camera_speed;
+        }
+        if is_key_down(KeyCode::D) {
+            camera_pos.x += camera_speed;
+        }
+        let camera = Camera2D {
+            zoom: vec2(scale * 2.0 / screen_width(), scale * 2.0 / screen_height()),
+            target: camera_pos,
+            ..Default::default()
+        };
+
+
+        clear_background(WHITE);
+        set_camera(&camera);
+        if let Some(stats) = game.update(get_frame_time()) {
+            println!(
+                "Fim da partida: {:?} venceu por {} x {} ({} x {} rebatidas, maior rally {}, {:.1}s)",
+                stats.winner,
+                stats.points.player1,
+                stats.points.player2,
+                stats.hits.player1,
+                stats.hits.player2,
+                stats.longest_rally,
+                game.elapsed()
+            );
+            if let (Some(path), Some(frames)) = (&record_path, &recording) {
+                flush_recording(path, frames);
+            }
+            match_seed = match_seed.wrapping_add(1);
+            println!("Partida com semente {}", match_seed);
+            game = Pong::with_seed(
+                GameWindow::new(FIELD_WIDTH, FIELD_HEIGHT),
+                player1_controller(&nn, recording.as_ref(), latency),
+                player2_controller(&my_champion, latency),
+                FIELD_POSITION,
+                match_seed,
+            );
+            game.rule = rule;
+        } else if let (Some(path), Some(frames)) = (&record_path, &recording) {
+            if frames.lock().unwrap().len() >= RECORD_FLUSH_FRAMES {
+                flush_recording(path, frames);
+            }
+        }
+        pong_drawer.draw(&game);
+        drawer.draw(&mut nn.lock().unwrap());
+        drawer2.draw(&mut my_champion.lock().unwrap());
+
+        next_frame().await;
+    }
+}
+
+
+
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Argumentos de linha de comando no formato `[subcomando] [posicionais...] [--chave valor] [--flag]`.
+///
+/// Propositalmente simples: uma `--chave` seguida de algo que não começa com `--` recebe
+/// esse valor; caso contrário é tratada como flag.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    pub positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    /// Lê os argumentos do processo atual (sem o nome do executável).
+    pub fn from_env() -> Self {
+        Args::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Self {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(key) => {
+                    let value = args.next_if(|next| !next.starts_with("--"));
+                    parsed.options.insert(key.to_owned(), value);
+                }
+                None => parsed.positional.push(arg),
+            }
+        }
+        parsed
+    }
+
+    /// Retorna o valor de `--key`, se foi passado com valor.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.options.get(key).and_then(|value| value.as_deref())
+    }
+
+    /// Indica se `--key` foi passado (com ou sem valor).
+    pub fn flag(&self, key: &str) -> bool {
+        self.options.contains_key(key)
+    }
+
+    /// Converte o valor de `--key` para `T`, com uma mensagem de erro legível.
+    pub fn parse_value<T: FromStr>(&self, key: &str) -> Result<Option<T>, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match self.value(key) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|e| format!("valor inválido para --{}: '{}' ({})", key, value, e)),
+            None if self.flag(key) => Err(format!("--{} precisa de um valor", key)),
+            None => Ok(None),
+        }
+    }
+
+    /// Converte uma lista separada por vírgulas (`--key a,b,c`) para `Vec<T>`.
+    pub fn parse_list<T: FromStr>(&self, key: &str) -> Result<Option<Vec<T>>, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        let Some(value) = self.value(key) else {
+            return self.parse_value::<String>(key).map(|_| None);
+        };
+        value
+            .split(',')
+            .map(|item| {
+                item.trim()
+                    .parse()
+                    .map_err(|e| format!("valor inválido em --{}: '{}' ({})", key, item, e))
+            })
+            .collect::<Result<Vec<T>, String>>()
+            .map(Some)
+    }
+}
@@ -1,26 +1,48 @@
+use std::collections::VecDeque;
+
 use macroquad::input::{is_key_down, KeyCode};
 use macroquad::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::pong::{GameWindow, FIXED_DT, PLAYER_HEIGHT, PLAYER_WIDTH};
 
-use super::pong::PLAYER_VELOCITY;
+/// Tempo de reação típico de uma pessoa, em segundos.
+pub const REACTION_TIME: f32 = 0.16;
 
 /// Representa a direção do jogador.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlayerDirection {
     Up,
     Down,
     None,
 }
 
+/// O resto do campo, além da bola e do próprio jogador: onde está o adversário e onde fica
+/// o campo na tela.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldView {
+    pub opponent_position: Vec2,
+    /// Canto superior esquerdo do campo.
+    pub position: Vec2,
+    pub window: GameWindow,
+}
+
 /// Define o comportamento dos controladores.
-pub trait Controller {
+/// Controladores são `Send` para que jogos inteiros possam ser simulados em outras threads.
+pub trait Controller: Send {
     /// Retorna a direção desejada com base nos inputs.
     /// Você pode usar Option<Vec2> caso precise da posição da bola ou do jogador.
+    /// É chamado uma vez por passo fixo da simulação; `ball_velocity` está em unidades por segundo.
     fn get_input(
         &mut self,
         ball_position: Vec2,
         ball_velocity: Vec2,
         player_position: Vec2,
     ) -> (PlayerDirection, f64); // velociade do jogador
+
+    /// Chamado a cada passo, antes de `get_input`, com o resto do campo. Só os controladores
+    /// que precisam dessas informações (ex.: uma rede que observa o adversário) o implementam.
+    fn observe_field(&mut self, _field: &FieldView) {}
 }
 
 /// Chaves de controle disponíveis.
@@ -65,3 +87,273 @@ impl Controller for HumanController {
         (pd, 1.0 )
     }
 }
+
+/// Controlador automático que apenas acompanha a altura da bola.
+/// Serve de adversário fixo no treino: a partida só termina quando o outro jogador erra.
+pub struct BotController;
+
+impl BotController {
+    pub fn new() -> Self {
+        BotController
+    }
+}
+
+impl Default for BotController {
+    fn default() -> Self {
+        BotController::new()
+    }
+}
+
+impl Controller for BotController {
+    fn get_input(
+        &mut self,
+        ball_position: Vec2,
+        _ball_velocity: Vec2,
+        player_position: Vec2,
+    ) -> (PlayerDirection, f64) {
+        let player_center = player_position.y + PLAYER_HEIGHT / 2.0;
+        let offset = ball_position.y - player_center;
+        // Zona morta para o bot não tremer quando já está alinhado com a bola
+        if offset.abs() < PLAYER_HEIGHT / 4.0 {
+            (PlayerDirection::None, 0.0)
+        } else if offset < 0.0 {
+            (PlayerDirection::Up, 1.0)
+        } else {
+            (PlayerDirection::Down, 1.0)
+        }
+    }
+}
+
+/// Espelha o campo na horizontal para `inner`, que então joga do lado direito como se
+/// estivesse no esquerdo. Permite usar como jogador 2 uma rede treinada como jogador 1.
+pub struct MirroredController<C: Controller> {
+    inner: C,
+    /// Coordenada x do centro do campo, eixo do espelhamento.
+    center_x: f32,
+}
+
+impl<C: Controller> MirroredController<C> {
+    pub fn new(inner: C, center_x: f32) -> Self {
+        MirroredController { inner, center_x }
+    }
+}
+
+impl<C: Controller> Controller for MirroredController<C> {
+    fn get_input(
+        &mut self,
+        ball_position: Vec2,
+        ball_velocity: Vec2,
+        player_position: Vec2,
+    ) -> (PlayerDirection, f64) {
+        let ball_position = Vec2::new(2.0 * self.center_x - ball_position.x, ball_position.y);
+        let ball_velocity = Vec2::new(-ball_velocity.x, ball_velocity.y);
+        // A posição do jogador é a borda esquerda do retângulo, que vira a direita
+        let player_position = Vec2::new(
+            2.0 * self.center_x - player_position.x - PLAYER_WIDTH,
+            player_position.y,
+        );
+        self.inner.get_input(ball_position, ball_velocity, player_position)
+    }
+
+    fn observe_field(&mut self, field: &FieldView) {
+        let opponent_position = Vec2::new(
+            2.0 * self.center_x - field.opponent_position.x - PLAYER_WIDTH,
+            field.opponent_position.y,
+        );
+        self.inner.observe_field(&FieldView {
+            opponent_position,
+            ..*field
+        });
+    }
+}
+
+/// Limites de percepção impostos a um controlador: ele enxerga a bola e o adversário com
+/// atraso e só decide de tempos em tempos, como uma pessoa. O padrão não impõe limite.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Latency {
+    /// Idade, em segundos, do que o controlador enxerga (ex.: `REACTION_TIME`).
+    pub delay: f32,
+    /// Intervalo, em segundos, entre duas decisões; entre elas a última é repetida.
+    pub decision_interval: f32,
+}
+
+impl Latency {
+    pub fn new(delay: f32, decision_interval: f32) -> Self {
+        Latency {
+            delay,
+            decision_interval,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = |seconds: f32| seconds.is_finite() && seconds >= 0.0;
+        if !valid(self.delay) || !valid(self.decision_interval) {
+            return Err("latency: delay e decision_interval devem ser números finitos e não negativos".into());
+        }
+        Ok(())
+    }
+
+    /// Atraso em passos da simulação.
+    fn delay_steps(&self) -> usize {
+        (self.delay / FIXED_DT).round() as usize
+    }
+
+    /// Passos entre duas decisões; ao menos um.
+    fn decision_steps(&self) -> usize {
+        ((self.decision_interval / FIXED_DT).round() as usize).max(1)
+    }
+
+    /// Indica se a latência não muda nada: sem atraso e decidindo a cada passo.
+    pub fn is_none(&self) -> bool {
+        self.delay_steps() == 0 && self.decision_steps() == 1
+    }
+
+    /// Aplica a latência a `inner`. Sem latência, `inner` é devolvido como está.
+    pub fn wrap<C: Controller + 'static>(self, inner: C) -> Box<dyn Controller> {
+        if self.is_none() {
+            Box::new(inner)
+        } else {
+            Box::new(DelayedController::new(inner, self))
+        }
+    }
+}
+
+/// O que o controlador atrasado enxergou num passo.
+#[derive(Debug, Clone, Copy)]
+struct Perception {
+    ball_position: Vec2,
+    ball_velocity: Vec2,
+    field: Option<FieldView>,
+}
+
+/// Entrega a `inner` a bola e o adversário de `latency.delay` segundos atrás e só pede uma
+/// nova decisão a cada `latency.decision_interval`. A posição do próprio jogador é sempre a
+/// atual. O tempo é contado em passos fixos, então a partida continua reproduzível.
+pub struct DelayedController<C: Controller> {
+    inner: C,
+    delay_steps: usize,
+    decision_steps: usize,
+    /// Percepções dos últimos `delay_steps + 1` passos, da mais antiga para a atual.
+    history: VecDeque<Perception>,
+    /// Campo recebido em `observe_field`, ainda não guardado no histórico.
+    field: Option<FieldView>,
+    action: (PlayerDirection, f64),
+    steps: usize,
+}
+
+impl<C: Controller> DelayedController<C> {
+    pub fn new(inner: C, latency: Latency) -> Self {
+        let delay_steps = latency.delay_steps();
+        DelayedController {
+            inner,
+            delay_steps,
+            decision_steps: latency.decision_steps(),
+            history: VecDeque::with_capacity(delay_steps + 1),
+            field: None,
+            action: (PlayerDirection::None, 0.0),
+            steps: 0,
+        }
+    }
+}
+
+impl<C: Controller> Controller for DelayedController<C> {
+    fn get_input(
+        &mut self,
+        ball_position: Vec2,
+        ball_velocity: Vec2,
+        player_position: Vec2,
+    ) -> (PlayerDirection, f64) {
+        self.history.push_back(Perception {
+            ball_position,
+            ball_velocity,
+            field: self.field.take(),
+        });
+        if self.history.len() > self.delay_steps + 1 {
+            self.history.pop_front();
+        }
+
+        // No começo da partida ainda não há histórico suficiente: vale o passo mais antigo
+        if self.steps.is_multiple_of(self.decision_steps) {
+            let seen = self.history[0];
+            if let Some(field) = &seen.field {
+                self.inner.observe_field(field);
+            }
+            self.action = self.inner.get_input(seen.ball_position, seen.ball_velocity, player_position);
+        }
+        self.steps += 1;
+        self.action
+    }
+
+    fn observe_field(&mut self, field: &FieldView) {
+        self.field = Some(*field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Devolve como velocidade o x da bola que recebeu e guarda o que viu a cada decisão.
+    #[derive(Default)]
+    struct Echo {
+        /// x da bola e do adversário vistos em cada chamada de `get_input`.
+        seen: Vec<(f32, Option<f32>)>,
+        opponent: Option<f32>,
+    }
+
+    impl Controller for Echo {
+        fn get_input(&mut self, ball_position: Vec2, _: Vec2, _: Vec2) -> (PlayerDirection, f64) {
+            self.seen.push((ball_position.x, self.opponent));
+            (PlayerDirection::Up, ball_position.x as f64)
+        }
+
+        fn observe_field(&mut self, field: &FieldView) {
+            self.opponent = Some(field.opponent_position.x);
+        }
+    }
+
+    /// Campo e bola do passo `step`, identificados pelo x.
+    fn play_step(controller: &mut impl Controller, step: usize) -> (PlayerDirection, f64) {
+        let x = step as f32;
+        controller.observe_field(&FieldView {
+            opponent_position: Vec2::new(x, 0.0),
+            position: Vec2::ZERO,
+            window: GameWindow::new(500.0, 400.0),
+        });
+        controller.get_input(Vec2::new(x, 0.0), Vec2::ZERO, Vec2::ZERO)
+    }
+
+    #[test]
+    fn delayed_controller_sees_the_past_and_decides_at_intervals() {
+        let latency = Latency::new(3.0 * FIXED_DT, 2.0 * FIXED_DT);
+        assert_eq!((latency.delay_steps(), latency.decision_steps()), (3, 2));
+        let mut delayed = DelayedController::new(Echo::default(), latency);
+
+        for step in 0..12 {
+            let (direction, speed) = play_step(&mut delayed, step);
+            // A última decisão foi no passo par mais recente, com o que havia 3 passos antes dele
+            let decision = step - step % 2;
+            let seen = decision.saturating_sub(3) as f64;
+            assert_eq!(direction, PlayerDirection::Up);
+            assert_eq!(speed, seen, "passo {}", step);
+        }
+
+        let expected: Vec<(f32, Option<f32>)> = (0..12)
+            .step_by(2)
+            .map(|decision: usize| {
+                let seen = decision.saturating_sub(3) as f32;
+                (seen, Some(seen))
+            })
+            .collect();
+        assert_eq!(delayed.inner.seen, expected);
+    }
+
+    #[test]
+    fn latency_rounded_to_zero_steps_is_none() {
+        assert!(Latency::default().is_none());
+        assert!(Latency::new(0.004, 0.0).is_none());
+        assert!(!Latency::new(REACTION_TIME, 0.0).is_none());
+        assert!(!Latency::new(0.0, 0.1).is_none());
+    }
+}
@@ -0,0 +1,252 @@
+//! API de ambiente no estilo Gym em volta do `Pong`: qualquer aprendiz (genético, ES,
+//! reforço ou um script) joga chamando `reset` e `step`, sem implementar `Controller`.
+//!
+//! No `PongEnv` o agente controla o jogador 1, um passo fixo de simulação por ação;
+//! o adversário é um `Controller` comum (o `BotController` por padrão).
+
+use std::sync::{Arc, Mutex};
+
+use macroquad::math::{vec2, Vec2};
+
+use crate::game::controller::{BotController, Controller, PlayerDirection};
+use crate::game::observation::{Observation, ObservationConfig};
+use crate::game::pong::{GameWindow, MatchRule, Pong, Winner};
+
+/// Recompensa por rebater a bola.
+pub const HIT_REWARD: f64 = 1.0;
+/// Recompensa por sofrer um ponto.
+pub const CONCEDED_REWARD: f64 = -1.0;
+
+/// Ação do agente num passo: direção e velocidade, como a de um `Controller`.
+pub type Action = (PlayerDirection, f64);
+
+/// Informações extras de um passo, além da recompensa.
+#[derive(Debug, Clone, Copy)]
+pub struct StepInfo {
+    /// Rebatidas do agente no episódio.
+    pub hits: i32,
+    /// Rebatidas do adversário no episódio.
+    pub opponent_hits: i32,
+    /// Pontos marcados pelo agente no episódio.
+    pub points_won: i32,
+    /// Pontos sofridos pelo agente no episódio.
+    pub points_conceded: i32,
+    /// Passos jogados no episódio.
+    pub steps: usize,
+    /// Vencedor da partida, se o episódio acabou com o fim dela.
+    pub winner: Option<Winner>,
+}
+
+/// Um ambiente de episódios: `reset` começa um episódio e `step` avança um passo.
+pub trait Environment {
+    type Action;
+
+    /// Tamanho do vetor de observação.
+    fn observation_size(&self) -> usize;
+
+    /// Começa um episódio novo a partir de `seed` e retorna a primeira observação.
+    fn reset(&mut self, seed: u64) -> Observation;
+
+    /// Aplica `action` e retorna a nova observação, a recompensa, se o episódio acabou
+    /// e as informações do passo.
+    fn step(&mut self, action: Self::Action) -> (Observation, f64, bool, StepInfo);
+}
+
+/// Joga um episódio inteiro com a política `policy`. Retorna a recompensa total e as
+/// informações do último passo.
+pub fn run_episode<E: Environment>(
+    env: &mut E,
+    seed: u64,
+    mut policy: impl FnMut(&Observation) -> E::Action,
+) -> (f64, StepInfo) {
+    let mut observation = env.reset(seed);
+    let mut total = 0.0;
+    loop {
+        let (next, reward, done, info) = env.step(policy(&observation));
+        total += reward;
+        if done {
+            return (total, info);
+        }
+        observation = next;
+    }
+}
+
+/// Controlador do jogador 1 que só repete a ação definida pelo ambiente.
+struct ActionController {
+    action: Arc<Mutex<Action>>,
+}
+
+impl Controller for ActionController {
+    fn get_input(&mut self, _ball_position: Vec2, _ball_velocity: Vec2, _player_position: Vec2) -> Action {
+        *self.action.lock().unwrap()
+    }
+}
+
+/// Uma partida de Pong por episódio, com a regra `rule` (um ponto, por padrão).
+pub struct PongEnv {
+    window: GameWindow,
+    pong: Pong,
+    action: Arc<Mutex<Action>>,
+    observation: ObservationConfig,
+    /// Regra das partidas, aplicada a partir do próximo `reset`.
+    pub rule: MatchRule,
+    /// Limite de passos por episódio.
+    pub max_steps: usize,
+    steps: usize,
+}
+
+impl PongEnv {
+    /// Ambiente contra o `BotController`.
+    pub fn new(observation: ObservationConfig, max_steps: usize) -> Self {
+        Self::with_opponent(observation, max_steps, Box::new(BotController::new()))
+    }
+
+    /// Ambiente contra `opponent`, que é mantido entre os episódios.
+    pub fn with_opponent(observation: ObservationConfig, max_steps: usize, opponent: Box<dyn Controller>) -> Self {
+        let action = Arc::new(Mutex::new((PlayerDirection::None, 0.0)));
+        let window = GameWindow::new(500.0, 400.0);
+        PongEnv {
+            window,
+            pong: Self::new_pong(window, &action, opponent, 0),
+            action,
+            observation,
+            rule: MatchRule::default(),
+            max_steps,
+            steps: 0,
+        }
+    }
+
+    fn new_pong(window: GameWindow, action: &Arc<Mutex<Action>>, opponent: Box<dyn Controller>, seed: u64) -> Pong {
+        Pong::with_seed(
+            window,
+            Box::new(ActionController { action: action.clone() }),
+            opponent,
+            vec2(0.0, 0.0),
+            seed,
+        )
+    }
+
+    /// A partida do episódio atual, para desenhar ou inspecionar.
+    pub fn pong(&self) -> &Pong {
+        &self.pong
+    }
+
+    fn info(&self, winner: Option<Winner>) -> StepInfo {
+        StepInfo {
+            hits: self.pong.hits.player1,
+            opponent_hits: self.pong.hits.player2,
+            points_won: self.pong.points.player1,
+            points_conceded: self.pong.points.player2,
+            steps: self.steps,
+            winner,
+        }
+    }
+}
+
+impl Environment for PongEnv {
+    type Action = Action;
+
+    fn observation_size(&self) -> usize {
+        self.observation.size()
+    }
+
+    fn reset(&mut self, seed: u64) -> Observation {
+        // O adversário passa para a partida nova
+        let opponent = std::mem::replace(&mut self.pong.player2.controller, Box::new(BotController::new()));
+        self.pong = Self::new_pong(self.window, &self.action, opponent, seed);
+        self.pong.rule = self.rule;
+        self.steps = 0;
+        self.observation.observe(&self.pong)
+    }
+
+    fn step(&mut self, action: Action) -> (Observation, f64, bool, StepInfo) {
+        *self.action.lock().unwrap() = action;
+        let hits_before = self.pong.hits.player1;
+        let conceded_before = self.pong.points.player2;
+        let result = self.pong.step();
+        self.steps += 1;
+
+        let mut reward = 0.0;
+        if self.pong.hits.player1 > hits_before {
+            reward += HIT_REWARD;
+        }
+        if self.pong.points.player2 > conceded_before {
+            reward += CONCEDED_REWARD;
+        }
+        let winner = result.map(|stats| stats.winner);
+        let done = result.is_some() || self.steps >= self.max_steps;
+        (self.observation.observe(&self.pong), reward, done, self.info(winner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::pong::{BALL_RADIUS, PLAYER_HEIGHT, PLAYER_WIDTH};
+
+    const STAY: Action = (PlayerDirection::None, 0.0);
+
+    fn env(max_steps: usize) -> PongEnv {
+        PongEnv::new(ObservationConfig::default(), max_steps)
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_first_observation() {
+        let mut a = env(100);
+        let mut b = env(100);
+        let first = a.reset(7);
+        assert_eq!(first, b.reset(7));
+        // Depois de jogar, o mesmo `reset` volta ao mesmo começo
+        a.step(STAY);
+        assert_eq!(a.reset(7), first);
+        assert_ne!(a.reset(8), first);
+    }
+
+    #[test]
+    fn hitting_the_ball_is_rewarded() {
+        let mut env = env(100);
+        env.reset(1);
+        let paddle = env.pong.player1.position;
+        env.pong.ball.position = Vec2::new(paddle.x + PLAYER_WIDTH, paddle.y + PLAYER_HEIGHT / 2.0);
+        env.pong.ball.velocity = Vec2::new(-480.0, 0.0);
+
+        let (_, reward, done, info) = env.step(STAY);
+        assert_eq!(reward, HIT_REWARD);
+        assert!(!done);
+        assert_eq!(info.hits, 1);
+    }
+
+    #[test]
+    fn conceding_a_point_is_punished_and_ends_the_episode() {
+        let mut env = env(100);
+        env.reset(1);
+        // Bola atrás da raquete, longe dela na vertical, indo para a parede esquerda
+        let field = env.pong.position;
+        env.pong.ball.position = Vec2::new(field.x + BALL_RADIUS + 1.0, field.y + BALL_RADIUS + 5.0);
+        env.pong.ball.velocity = Vec2::new(-480.0, 0.0);
+
+        let (_, reward, done, info) = env.step(STAY);
+        assert_eq!(reward, CONCEDED_REWARD);
+        assert!(done);
+        assert_eq!(info.winner, Some(Winner::Player2));
+        assert_eq!(info.points_conceded, 1);
+    }
+
+    #[test]
+    fn max_steps_ends_the_episode() {
+        let mut env = env(5);
+        env.reset(3);
+        for _ in 0..4 {
+            let (_, _, done, _) = env.step(STAY);
+            assert!(!done);
+        }
+        let (_, _, done, info) = env.step(STAY);
+        assert!(done);
+        assert_eq!(info.steps, 5);
+        assert_eq!(info.winner, None);
+
+        let (total, info) = run_episode(&mut env, 3, |_| STAY);
+        assert_eq!(total, 0.0);
+        assert_eq!(info.steps, 5);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod pong;
-pub mod controller;
\ No newline at end of file
+pub mod controller;
+pub mod renderer;
+pub mod recording;
+pub mod env;
+pub mod observation;
\ No newline at end of file
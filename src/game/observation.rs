@@ -0,0 +1,199 @@
+//! Vetor de observação montado a partir do que um jogador enxerga do campo (`PlayerView`).
+//!
+//! O vetor é a concatenação das features escolhidas, na ordem da configuração. O padrão
+//! reproduz os quatro inputs originais da rede (`network_inputs`). A configuração fica
+//! gravada no arquivo da rede, então cada rede joga com as features com que foi treinada.
+
+use std::str::FromStr;
+
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::game::controller::FieldView;
+use crate::game::pong::{GameWindow, Pong, BALL_RADIUS, FIXED_DT, PLAYER_HEIGHT, PLAYER_WIDTH};
+use crate::neural_network::neural_network_f::network_inputs;
+
+/// Observação entregue a quem joga: um vetor de números.
+pub type Observation = Vec<f64>;
+
+/// Tudo o que um jogador enxerga num passo, do ponto de vista dele (jogando à esquerda).
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerView {
+    pub ball_position: Vec2,
+    /// Em unidades por segundo.
+    pub ball_velocity: Vec2,
+    pub player_position: Vec2,
+    pub field: FieldView,
+}
+
+impl PlayerView {
+    /// O campo visto pelo jogador 1 de `pong`.
+    pub fn player1(pong: &Pong) -> Self {
+        PlayerView {
+            ball_position: pong.ball.position,
+            ball_velocity: pong.ball.velocity,
+            player_position: pong.player1.position,
+            field: FieldView {
+                opponent_position: pong.player2.position,
+                position: pong.position,
+                window: pong.window,
+            },
+        }
+    }
+
+    /// Altura em que a bola vai cruzar a frente da raquete, relativa ao topo do campo,
+    /// contando as rebatidas nas paredes. Com a bola se afastando, o centro do campo.
+    fn predicted_intercept(&self) -> f32 {
+        let field = &self.field;
+        let target_x = self.player_position.x + PLAYER_WIDTH + BALL_RADIUS;
+        let velocity = self.ball_velocity;
+        if velocity.x >= 0.0 {
+            return field.window.height / 2.0;
+        }
+
+        let time = (target_x - self.ball_position.x) / velocity.x;
+        let y = self.ball_position.y - field.position.y + velocity.y * time.max(0.0);
+        // Desdobra as reflexões no topo e no fundo: a bola anda num corredor de altura `span`
+        let span = (field.window.height - 2.0 * BALL_RADIUS).max(1.0);
+        let mut offset = (y - BALL_RADIUS).rem_euclid(2.0 * span);
+        if offset > span {
+            offset = 2.0 * span - offset;
+        }
+        BALL_RADIUS + offset
+    }
+}
+
+/// Uma parte do vetor de observação.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObservationFeature {
+    /// Distâncias absolutas (x, y) entre o jogador e a bola.
+    BallDistance,
+    /// Velocidade (x, y) da bola, em unidades por passo.
+    BallVelocity,
+    /// Posição (x, y) da bola, relativa ao canto do campo.
+    BallPosition,
+    /// Distâncias (x, y) com sinal do centro da raquete até a bola: y negativo é bola acima.
+    SignedOffset,
+    /// Altura do próprio jogador, relativa ao topo do campo.
+    PaddleY,
+    /// Altura do adversário, relativa ao topo do campo.
+    OpponentY,
+    /// Altura prevista em que a bola chega à raquete, relativa ao topo do campo.
+    PredictedIntercept,
+}
+
+impl FromStr for ObservationFeature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ball-distance" => Ok(ObservationFeature::BallDistance),
+            "ball-velocity" => Ok(ObservationFeature::BallVelocity),
+            "ball-position" => Ok(ObservationFeature::BallPosition),
+            "signed-offset" => Ok(ObservationFeature::SignedOffset),
+            "paddle-y" => Ok(ObservationFeature::PaddleY),
+            "opponent-y" => Ok(ObservationFeature::OpponentY),
+            "predicted-intercept" => Ok(ObservationFeature::PredictedIntercept),
+            _ => Err(format!(
+                "feature desconhecida: {} (use ball-distance, ball-velocity, ball-position, signed-offset, \
+                 paddle-y, opponent-y ou predicted-intercept)",
+                s
+            )),
+        }
+    }
+}
+
+impl ObservationFeature {
+    /// Quantos números a feature ocupa no vetor.
+    pub fn size(self) -> usize {
+        match self {
+            ObservationFeature::BallDistance
+            | ObservationFeature::BallVelocity
+            | ObservationFeature::BallPosition
+            | ObservationFeature::SignedOffset => 2,
+            ObservationFeature::PaddleY
+            | ObservationFeature::OpponentY
+            | ObservationFeature::PredictedIntercept => 1,
+        }
+    }
+
+    /// Acrescenta a feature a `out`. Com `scale`, medidas horizontais são divididas pela
+    /// largura do campo e verticais pela altura (velocidades em campos por segundo).
+    fn write(self, view: &PlayerView, scale: Option<GameWindow>, out: &mut Observation) {
+        let field = &view.field;
+        let (x_scale, y_scale) = scale.map_or((1.0, 1.0), |window| (window.width, window.height));
+        let mut push_xy = |x: f32, y: f32| out.extend([(x / x_scale) as f64, (y / y_scale) as f64]);
+        match self {
+            ObservationFeature::BallDistance => {
+                let inputs = network_inputs(view.ball_position, view.ball_velocity, view.player_position);
+                push_xy(inputs[0] as f32, inputs[1] as f32);
+            }
+            ObservationFeature::BallVelocity => {
+                // Sem normalização a rede vê a velocidade por passo, como sempre viu
+                let velocity = match scale {
+                    Some(_) => view.ball_velocity,
+                    None => view.ball_velocity * FIXED_DT,
+                };
+                push_xy(velocity.x, velocity.y);
+            }
+            ObservationFeature::BallPosition => {
+                let position = view.ball_position - field.position;
+                push_xy(position.x, position.y);
+            }
+            ObservationFeature::SignedOffset => {
+                let paddle_center = view.player_position + Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT) / 2.0;
+                let offset = view.ball_position - paddle_center;
+                push_xy(offset.x, offset.y);
+            }
+            ObservationFeature::PaddleY => out.push(((view.player_position.y - field.position.y) / y_scale) as f64),
+            ObservationFeature::OpponentY => {
+                out.push(((field.opponent_position.y - field.position.y) / y_scale) as f64)
+            }
+            ObservationFeature::PredictedIntercept => out.push((view.predicted_intercept() / y_scale) as f64),
+        }
+    }
+}
+
+/// Quais features compõem a observação, em que ordem, e se são normalizadas.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObservationConfig {
+    pub features: Vec<ObservationFeature>,
+    /// Divide as medidas pelas dimensões do campo (`GameWindow`), deixando-as perto de [-1, 1].
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+impl Default for ObservationConfig {
+    fn default() -> Self {
+        ObservationConfig {
+            features: vec![ObservationFeature::BallDistance, ObservationFeature::BallVelocity],
+            normalize: false,
+        }
+    }
+}
+
+impl ObservationConfig {
+    pub fn new(features: Vec<ObservationFeature>, normalize: bool) -> Self {
+        ObservationConfig { features, normalize }
+    }
+
+    /// Tamanho do vetor de observação.
+    pub fn size(&self) -> usize {
+        self.features.iter().map(|feature| feature.size()).sum()
+    }
+
+    /// Monta a observação de `view`.
+    pub fn build(&self, view: &PlayerView) -> Observation {
+        let scale = self.normalize.then_some(view.field.window);
+        let mut observation = Vec::with_capacity(self.size());
+        for feature in &self.features {
+            feature.write(view, scale, &mut observation);
+        }
+        observation
+    }
+
+    /// Monta a observação do jogador 1 em `pong`.
+    pub fn observe(&self, pong: &Pong) -> Observation {
+        self.build(&PlayerView::player1(pong))
+    }
+}
@@ -1,7 +1,16 @@
-use ::rand::random_range;
-use macroquad::prelude::*;
+use std::str::FromStr;
+
+use ::rand::{Rng, SeedableRng};
+use macroquad::math::{Rect, Vec2};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game::controller::{Controller, FieldView, PlayerDirection};
+
+/// Gerador aleatório de uma partida. Toda a aleatoriedade do jogo (saque e desvio da bola)
+/// sai dele, então a mesma semente com os mesmos controladores reproduz a mesma partida.
+pub type MatchRng = ChaCha8Rng;
 
-use crate::game::controller::{Controller, PlayerDirection};
 #[derive(Debug, Clone, Copy)]
 pub struct GameWindow {
     pub width: f32,
@@ -14,12 +23,22 @@ impl GameWindow {
     }
 }
 
-pub const PLAYER_VELOCITY: f32 = 8.0;
-const PLAYER_HEIGHT: f32 = 70.0;
-const PLAYER_WIDTH: f32 = 10.0;
-const BALL_RADIUS: f32 = 10.0;
-
-struct Player {
+/// Passo fixo da simulação, em segundos. A física sempre avança neste passo,
+/// independente da taxa de quadros de quem está desenhando o jogo.
+pub const FIXED_DT: f32 = 1.0 / 60.0;
+/// Máximo de passos simulados por quadro, para o jogo não "espiralar" depois de um travamento.
+const MAX_STEPS_PER_FRAME: u32 = 8;
+/// Folga na comparação do acumulador: somando quadros em f32, um segundo a 144 Hz fica um
+/// pouco abaixo de 60 passos e perderia o último.
+const ACCUMULATOR_EPSILON: f32 = 1e-4;
+
+/// Velocidade máxima do jogador, em unidades por segundo.
+pub const PLAYER_VELOCITY: f32 = 480.0;
+pub const PLAYER_HEIGHT: f32 = 70.0;
+pub const PLAYER_WIDTH: f32 = 10.0;
+pub const BALL_RADIUS: f32 = 10.0;
+
+pub struct Player {
     pub position: Vec2,
     pub controller: Box<dyn Controller>,
 }
@@ -32,23 +51,32 @@ impl Player {
         }
     }
 
-    pub fn update(&mut self, ball_position: Vec2, ball_velocity: Vec2, player_position: Vec2) {
+    pub fn update(
+        &mut self,
+        ball_position: Vec2,
+        ball_velocity: Vec2,
+        player_position: Vec2,
+        field: &FieldView,
+        dt: f32,
+    ) {
+        self.controller.observe_field(field);
         let (direction, velocity) =
             self.controller
                 .get_input(ball_position, ball_velocity, player_position);
         match direction {
             PlayerDirection::Up => {
-                self.position.y -= (velocity as f32 * PLAYER_VELOCITY);
+                self.position.y -= velocity as f32 * PLAYER_VELOCITY * dt;
             }
             PlayerDirection::Down => {
-                self.position.y += (velocity as f32 * PLAYER_VELOCITY);
+                self.position.y += velocity as f32 * PLAYER_VELOCITY * dt;
             }
             PlayerDirection::None => {}
         }
     }
 }
 
-#[derive(Clone, Copy)]
+/// Um contador por jogador, usado tanto para rebatidas quanto para pontos.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Pontuation {
     pub player1: i32,
     pub player2: i32,
@@ -71,37 +99,128 @@ impl Pontuation {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Winner {
     Player1,
     Player2,
 }
 
+/// Resumo de uma partida encerrada.
+#[derive(Debug, Clone, Copy)]
 pub struct GameStats {
     pub winner: Winner,
-    pub pontuation: Pontuation,
+    /// Rebatidas de cada jogador.
+    pub hits: Pontuation,
+    /// Pontos de cada jogador.
+    pub points: Pontuation,
+    /// Maior rally, em rebatidas dos dois jogadores.
+    pub longest_rally: i32,
+    /// Duração da partida, em passos de `FIXED_DT`.
+    pub steps: usize,
+}
+
+/// Quando uma partida termina.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MatchRule {
+    /// Vence quem marcar esta quantidade de pontos primeiro.
+    FirstTo(i32),
+    /// A partida dura esta quantidade de segundos e vence quem tiver mais pontos;
+    /// empatada no fim do tempo, o próximo ponto decide.
+    Timed(f32),
+}
+
+impl Default for MatchRule {
+    /// Um ponto por partida.
+    fn default() -> Self {
+        MatchRule::FirstTo(1)
+    }
+}
+
+impl FromStr for MatchRule {
+    type Err = String;
+
+    /// Aceita `first-to:N` ou `timed:SEGUNDOS`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("regra de partida inválida: {} (use first-to:N ou timed:SEGUNDOS)", s);
+        let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+        match kind.to_ascii_lowercase().as_str() {
+            "first-to" => match value.parse() {
+                Ok(points) if points > 0 => Ok(MatchRule::FirstTo(points)),
+                _ => Err(invalid()),
+            },
+            "timed" => match value.parse() {
+                Ok(seconds) if seconds > 0.0 => Ok(MatchRule::Timed(seconds)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
 }
 
+/// Simulação do Pong, sem nenhuma dependência de janela ou contexto gráfico.
+/// A física avança sempre em passos fixos de `FIXED_DT` (`step`); `update` acumula o tempo
+/// de quadro e executa quantos passos couberem. O desenho fica a cargo de
+/// `game::renderer::PongDrawer`.
 pub struct Pong {
     pub window: GameWindow,
     /// Posição do canto superior esquerdo do campo na tela
     pub position: Vec2,
     pub player1: Player,
     pub player2: Player,
-    pub pontuation: Pontuation,
+    /// Rebatidas de cada jogador.
+    pub hits: Pontuation,
+    /// Pontos de cada jogador.
+    pub points: Pontuation,
+    /// Regra que encerra a partida; o padrão é um ponto por partida.
+    pub rule: MatchRule,
     pub player_scale: Vec2,
     pub ball: Ball,
     pub finished: bool,
+    /// Passos já simulados.
+    pub steps: usize,
+    /// Rebatidas no rally atual e o maior rally da partida.
+    rally: i32,
+    longest_rally: i32,
+    /// Sinal da velocidade horizontal do último saque.
+    ball_serve_direction: f32,
+    rng: MatchRng,
+    /// Tempo de quadro acumulado que ainda não virou um passo de simulação.
+    accumulator: f32,
 }
 
 impl Pong {
     /// Cria um Pong em determinada posição na tela,
     /// com tamanho definido por `window.width` e `window.height`.
+    /// A partida usa uma semente aleatória; para reproduzir um jogo use `with_seed`.
     pub fn new(
         window: GameWindow,
         p1_controller: Box<dyn Controller>,
         p2_controller: Box<dyn Controller>,
         position: Vec2,
+    ) -> Self {
+        let rng = MatchRng::from_rng(&mut ::rand::rng());
+        Self::with_rng(window, p1_controller, p2_controller, position, rng)
+    }
+
+    /// Cria um Pong cuja aleatoriedade é totalmente determinada por `seed`.
+    pub fn with_seed(
+        window: GameWindow,
+        p1_controller: Box<dyn Controller>,
+        p2_controller: Box<dyn Controller>,
+        position: Vec2,
+        seed: u64,
+    ) -> Self {
+        let rng = MatchRng::seed_from_u64(seed);
+        Self::with_rng(window, p1_controller, p2_controller, position, rng)
+    }
+
+    /// Cria um Pong usando o gerador `rng` para o saque e os desvios da bola.
+    pub fn with_rng(
+        window: GameWindow,
+        p1_controller: Box<dyn Controller>,
+        p2_controller: Box<dyn Controller>,
+        position: Vec2,
+        mut rng: MatchRng,
     ) -> Self {
         let player_scale = Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT);
 
@@ -120,7 +239,8 @@ impl Pong {
             position.x + window.width / 2.0,
             position.y + window.height / 2.0,
         );
-        let ball = Ball::new(ball_start_pos);
+        let ball = Ball::new(ball_start_pos, &mut rng);
+        let ball_serve_direction = ball.velocity.x.signum();
 
         Pong {
             window,
@@ -128,82 +248,130 @@ impl Pong {
             player1: Player::new(player1_pos, p1_controller),
             player2: Player::new(player2_pos, p2_controller),
             player_scale,
-            pontuation: Pontuation::new(),
+            hits: Pontuation::new(),
+            points: Pontuation::new(),
+            rule: MatchRule::default(),
             ball,
             finished: false,
+            steps: 0,
+            rally: 0,
+            longest_rally: 0,
+            ball_serve_direction,
+            rng,
+            accumulator: 0.0,
         }
     }
 
-    /// Atualiza o jogo e retorna Some(GameStats) se terminou (por pontuação), ou None se continua.
-    pub fn update(&mut self) -> Option<GameStats> {
-        let dt = get_frame_time();
+    /// Acumula `frame_time` segundos e executa os passos fixos correspondentes.
+    /// Retorna Some(GameStats) se a partida terminou em algum desses passos,
+    /// ou None se continua.
+    pub fn update(&mut self, frame_time: f32) -> Option<GameStats> {
+        self.accumulator += frame_time;
+        let mut steps = 0;
+        while self.accumulator + ACCUMULATOR_EPSILON >= FIXED_DT {
+            if steps == MAX_STEPS_PER_FRAME {
+                self.accumulator = 0.0;
+                break;
+            }
+            self.accumulator -= FIXED_DT;
+            steps += 1;
+            if let Some(stats) = self.step() {
+                self.accumulator = 0.0;
+                return Some(stats);
+            }
+        }
+        None
+    }
+
+    /// Avança a simulação exatamente um passo de `FIXED_DT` segundos.
+    /// Retorna Some(GameStats) se a partida terminou segundo `rule`, ou None se continua.
+    pub fn step(&mut self) -> Option<GameStats> {
+        let dt = FIXED_DT;
+        self.steps += 1;
+        // Cada jogador vê o adversário na posição do início do passo
+        let field1 = self.field_view(self.player2.position);
+        let field2 = self.field_view(self.player1.position);
         self.player1.update(
             self.ball.position,
             self.ball.velocity,
             self.player1.position,
+            &field1,
+            dt,
+        );
+        self.player2.update(
+            self.ball.position,
+            self.ball.velocity,
+            self.player2.position,
+            &field2,
+            dt,
         );
-        self.player2.update(self.ball.position, self.ball.velocity, self.player2.position);
         self.ball.update_position(dt);
 
-        match self.check_collision() {
-            Some(winner) => {
-                // Reinicia a bola no centro após pontuação
-                self.ball = Ball::new(Vec2::new(
-                    self.position.x + self.window.width / 2.0,
-                    self.position.y + self.window.height / 2.0,
-                ));
-                Some(GameStats {
-                    winner,
-                    pontuation: self.pontuation,
-                })
+        if let Some(scorer) = self.check_collision() {
+            match scorer {
+                Winner::Player1 => self.points.increase_p1_score(),
+                Winner::Player2 => self.points.increase_p2_score(),
             }
-            None => None,
+            self.rally = 0;
+            self.serve();
         }
-    }
 
-    //desenha o game (player, bal, campo)
-    pub fn draw(&self) {
-        // Desenha o campo
+        let winner = self.match_winner()?;
+        self.finished = true;
+        Some(GameStats {
+            winner,
+            hits: self.hits,
+            points: self.points,
+            longest_rally: self.longest_rally,
+            steps: self.steps,
+        })
+    }
 
-        draw_rectangle_lines(
-            self.position.x,
-            self.position.y,
-            self.window.width,
-            self.window.height,
-            5.0,
-            BLACK,
+    /// Reinicia a bola no centro após um ponto. O saque vai para o lado oposto ao do
+    /// saque anterior, alternando entre os jogadores.
+    fn serve(&mut self) {
+        let previous_direction = self.ball_serve_direction;
+        self.ball = Ball::new(
+            Vec2::new(
+                self.position.x + self.window.width / 2.0,
+                self.position.y + self.window.height / 2.0,
+            ),
+            &mut self.rng,
         );
+        self.ball.velocity.x = -previous_direction * self.ball.velocity.x.abs();
+        self.ball_serve_direction = self.ball.velocity.x.signum();
+    }
 
-        // Desenha cada jogador
-        draw_rectangle(
-            self.player1.position.x,
-            self.player1.position.y,
-            self.player_scale.x,
-            self.player_scale.y,
-            BLACK,
-        );
-        draw_rectangle(
-            self.player2.position.x,
-            self.player2.position.y,
-            self.player_scale.x,
-            self.player_scale.y,
-            BLACK,
-        );
+    /// Vencedor da partida, se ela já terminou segundo `rule`.
+    fn match_winner(&self) -> Option<Winner> {
+        let leader = match self.points.player1.cmp(&self.points.player2) {
+            std::cmp::Ordering::Greater => Winner::Player1,
+            std::cmp::Ordering::Less => Winner::Player2,
+            std::cmp::Ordering::Equal => return None,
+        };
+        let over = match self.rule {
+            MatchRule::FirstTo(points) => self.points.player1.max(self.points.player2) >= points,
+            MatchRule::Timed(seconds) => self.steps as f32 * FIXED_DT >= seconds,
+        };
+        over.then_some(leader)
+    }
 
-        // Escreve a pontuação
-        draw_text(
-            &format!(
-                "Pontuação p1: {}  p2: {}",
-                self.pontuation.player1, self.pontuation.player2
-            ),
-            self.position.x + 100.0,
-            self.position.y - 20.0, // Desenha acima do campo, por exemplo
-            30.0,
-            PINK,
-        );
+    fn field_view(&self, opponent_position: Vec2) -> FieldView {
+        FieldView {
+            opponent_position,
+            position: self.position,
+            window: self.window,
+        }
+    }
+
+    /// Tempo de partida já jogado, em segundos.
+    pub fn elapsed(&self) -> f32 {
+        self.steps as f32 * FIXED_DT
+    }
 
-        // Desenha a bola
-        self.ball.draw();
+    /// Maior rally da partida até agora, em rebatidas dos dois jogadores.
+    pub fn longest_rally(&self) -> i32 {
+        self.longest_rally
     }
 
     //checa colisões de player e bola e retorna o vencedor caso haja uma colisão fatal
@@ -213,13 +381,11 @@ impl Pong {
         let top_wall = self.position.y;
         let bottom_wall = self.position.y + self.window.height;
 
-        // Se a bola sair pela esquerda ou direita, declara vencedor
+        // Se a bola sair pela esquerda ou direita, o outro jogador marca o ponto
         if self.ball.position.x - BALL_RADIUS <= left_wall {
-            self.finished = true;
             return Some(Winner::Player2);
         }
         if self.ball.position.x + BALL_RADIUS >= right_wall {
-            self.finished = true;
             return Some(Winner::Player1);
         }
 
@@ -238,8 +404,9 @@ impl Pong {
             PLAYER_HEIGHT,
         );
         if self.ball.collision_cooldown <= 0.0 && self.ball.rect().overlaps(&player1_rect) {
-            self.ball.invert_velocity_x();
-            self.pontuation.increase_p1_score();
+            self.ball.invert_velocity_x(&mut self.rng);
+            self.hits.increase_p1_score();
+            self.count_rally_hit();
             self.ball.collision_cooldown = 0.2;
             self.ball.position.x = self.player1.position.x + PLAYER_WIDTH + BALL_RADIUS;
         }
@@ -252,8 +419,9 @@ impl Pong {
             PLAYER_HEIGHT,
         );
         if self.ball.collision_cooldown <= 0.0 && self.ball.rect().overlaps(&player2_rect) {
-            self.ball.invert_velocity_x();
-            self.pontuation.increase_p2_score();
+            self.ball.invert_velocity_x(&mut self.rng);
+            self.hits.increase_p2_score();
+            self.count_rally_hit();
             self.ball.collision_cooldown = 0.2;
             self.ball.position.x = self.player2.position.x - BALL_RADIUS;
         }
@@ -274,21 +442,31 @@ impl Pong {
 
         None
     }
+
+    fn count_rally_hit(&mut self) {
+        self.rally += 1;
+        self.longest_rally = self.longest_rally.max(self.rally);
+    }
 }
 
-struct Ball {
+pub struct Ball {
     pub position: Vec2,
-    velocity: Vec2,
+    /// Velocidade em unidades por segundo.
+    pub velocity: Vec2,
     acceleration: Vec2,
     pub collision_cooldown: f32,
 }
 
-const MIN_HORIZONTAL_SPEED: f32 = 8.0;
+// Velocidades da bola em unidades por segundo
+const BALL_SPEED: f32 = 300.0;
+const MIN_HORIZONTAL_SPEED: f32 = 480.0;
+/// Desvio vertical máximo somado à bola a cada rebatida.
+const MAX_DEFLECTION: f32 = 120.0;
 
 impl Ball {
-    fn new(position: Vec2) -> Self {
-        let speed = 5.0;
-        let angle = random_range(0.0..std::f32::consts::TAU);
+    fn new<R: Rng + ?Sized>(position: Vec2, rng: &mut R) -> Self {
+        let speed = BALL_SPEED;
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
         let mut vx = angle.cos() * speed;
         let vy = angle.sin() * speed;
 
@@ -312,18 +490,14 @@ impl Ball {
 
     /// Atualiza posição e collision_cooldown
     fn update_position(&mut self, dt: f32) {
-        self.position += self.velocity;
-        self.velocity += self.acceleration;
+        self.position += self.velocity * dt;
+        self.velocity += self.acceleration * dt;
         if self.collision_cooldown > 0.0 {
             self.collision_cooldown -= dt;
         }
     }
 
-    fn draw(&self) {
-        draw_circle(self.position.x, self.position.y, BALL_RADIUS, RED);
-    }
-
-    fn rect(&self) -> Rect {
+    pub fn rect(&self) -> Rect {
         Rect::new(
             self.position.x - BALL_RADIUS,
             self.position.y - BALL_RADIUS,
@@ -332,15 +506,241 @@ impl Ball {
         )
     }
 
-    pub fn invert_velocity_x(&mut self) {
+    pub fn invert_velocity_x<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         self.velocity.x = -self.velocity.x;
         if self.velocity.x.abs() < MIN_HORIZONTAL_SPEED {
             self.velocity.x = self.velocity.x.signum() * MIN_HORIZONTAL_SPEED;
         }
-        self.velocity.y += random_range(-2.0..2.0);
+        self.velocity.y += rng.random_range(-MAX_DEFLECTION..MAX_DEFLECTION);
     }
 
     pub fn invert_velocity_y(&mut self) {
         self.velocity.y = -self.velocity.y;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::controller::BotController;
+
+    /// Controlador que nunca se mexe.
+    struct Still;
+
+    impl Controller for Still {
+        fn get_input(&mut self, _: Vec2, _: Vec2, _: Vec2) -> (PlayerDirection, f64) {
+            (PlayerDirection::None, 0.0)
+        }
+    }
+
+    fn still_game(seed: u64) -> Pong {
+        Pong::with_seed(
+            GameWindow::new(500.0, 400.0),
+            Box::new(Still),
+            Box::new(Still),
+            Vec2::new(100.0, 100.0),
+            seed,
+        )
+    }
+
+    /// Partida entre dois bots que não termina nos primeiros minutos.
+    fn bot_game(seed: u64) -> Pong {
+        let mut pong = Pong::with_seed(
+            GameWindow::new(500.0, 400.0),
+            Box::new(BotController::new()),
+            Box::new(BotController::new()),
+            Vec2::new(100.0, 100.0),
+            seed,
+        );
+        pong.rule = MatchRule::FirstTo(1000);
+        pong
+    }
+
+    /// Estado de cada passo de uma partida entre dois bots.
+    fn bot_trace(seed: u64, steps: usize) -> Vec<(Vec2, Vec2, Vec2, Vec2, i32, i32)> {
+        let mut pong = bot_game(seed);
+        (0..steps)
+            .map(|_| {
+                pong.step();
+                (
+                    pong.ball.position,
+                    pong.ball.velocity,
+                    pong.player1.position,
+                    pong.player2.position,
+                    pong.points.player1,
+                    pong.points.player2,
+                )
+            })
+            .collect()
+    }
+
+    fn field_center(pong: &Pong) -> Vec2 {
+        pong.position + Vec2::new(pong.window.width, pong.window.height) / 2.0
+    }
+
+    #[test]
+    fn ball_past_left_wall_scores_for_player2() {
+        let mut pong = still_game(1);
+        pong.ball.position = Vec2::new(pong.position.x + BALL_RADIUS - 1.0, field_center(&pong).y);
+        assert_eq!(pong.check_collision(), Some(Winner::Player2));
+    }
+
+    #[test]
+    fn ball_past_right_wall_scores_for_player1() {
+        let mut pong = still_game(1);
+        let right_wall = pong.position.x + pong.window.width;
+        pong.ball.position = Vec2::new(right_wall - BALL_RADIUS + 1.0, field_center(&pong).y);
+        assert_eq!(pong.check_collision(), Some(Winner::Player1));
+    }
+
+    #[test]
+    fn ball_bounces_on_top_and_bottom() {
+        let mut pong = still_game(1);
+        let center = field_center(&pong);
+
+        pong.ball.position = Vec2::new(center.x, pong.position.y + BALL_RADIUS - 1.0);
+        pong.ball.velocity = Vec2::new(300.0, -200.0);
+        assert_eq!(pong.check_collision(), None);
+        assert_eq!(pong.ball.velocity, Vec2::new(300.0, 200.0));
+
+        pong.ball.position = Vec2::new(center.x, pong.position.y + pong.window.height - BALL_RADIUS + 1.0);
+        assert_eq!(pong.check_collision(), None);
+        assert_eq!(pong.ball.velocity, Vec2::new(300.0, -200.0));
+    }
+
+    #[test]
+    fn paddle_hit_reflects_ball_and_waits_for_cooldown() {
+        let mut pong = still_game(1);
+        let paddle = pong.player1.position;
+        let inside_paddle = Vec2::new(paddle.x + PLAYER_WIDTH, paddle.y + PLAYER_HEIGHT / 2.0);
+
+        pong.ball.position = inside_paddle;
+        pong.ball.velocity = Vec2::new(-MIN_HORIZONTAL_SPEED, 0.0);
+        assert_eq!(pong.check_collision(), None);
+        assert!(pong.ball.velocity.x > 0.0);
+        assert_eq!(pong.hits.player1, 1);
+        assert_eq!(pong.ball.position.x, paddle.x + PLAYER_WIDTH + BALL_RADIUS);
+        assert!(pong.ball.collision_cooldown > 0.0);
+
+        // Durante o cooldown a bola atravessa a raquete sem contar outra rebatida
+        pong.ball.position = inside_paddle;
+        let velocity = pong.ball.velocity;
+        pong.check_collision();
+        assert_eq!(pong.hits.player1, 1);
+        assert_eq!(pong.ball.velocity, velocity);
+
+        pong.ball.collision_cooldown = 0.0;
+        pong.check_collision();
+        assert_eq!(pong.hits.player1, 2);
+    }
+
+    #[test]
+    fn paddles_are_clamped_to_the_field() {
+        let mut pong = still_game(1);
+        let top_wall = pong.position.y;
+        let bottom_wall = pong.position.y + pong.window.height;
+
+        pong.player1.position.y = top_wall - 50.0;
+        pong.player2.position.y = bottom_wall;
+        pong.check_collision();
+        assert_eq!(pong.player1.position.y, top_wall);
+        assert_eq!(pong.player2.position.y, bottom_wall - PLAYER_HEIGHT);
+
+        pong.player1.position.y = bottom_wall;
+        pong.player2.position.y = top_wall - 50.0;
+        pong.check_collision();
+        assert_eq!(pong.player1.position.y, bottom_wall - PLAYER_HEIGHT);
+        assert_eq!(pong.player2.position.y, top_wall);
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_match() {
+        let trace = bot_trace(42, 3000);
+        assert_eq!(trace, bot_trace(42, 3000));
+        // A partida precisa ter tido pontos para o teste dizer algo
+        let (.., p1, p2) = *trace.last().unwrap();
+        assert!(p1 + p2 > 0);
+        assert_ne!(trace, bot_trace(43, 3000));
+    }
+
+    /// Para a bola no centro, para que nenhum ponto aconteça sozinho.
+    fn freeze_ball(pong: &mut Pong) {
+        pong.ball.position = field_center(pong);
+        pong.ball.velocity = Vec2::ZERO;
+        pong.ball.acceleration = Vec2::ZERO;
+    }
+
+    #[test]
+    fn first_to_n_ends_exactly_at_n() {
+        let mut pong = still_game(3);
+        pong.rule = MatchRule::FirstTo(3);
+        for _ in 0..100_000 {
+            let leader = pong.points.player1.max(pong.points.player2);
+            if let Some(stats) = pong.step() {
+                assert_eq!(leader, 2);
+                assert_eq!(stats.points.player1.max(stats.points.player2), 3);
+                assert!(stats.points.player1.min(stats.points.player2) < 3);
+                assert!(pong.finished);
+                return;
+            }
+        }
+        panic!("a partida não terminou");
+    }
+
+    #[test]
+    fn timed_match_ends_at_its_deadline() {
+        let mut pong = still_game(1);
+        pong.rule = MatchRule::Timed(0.5);
+        freeze_ball(&mut pong);
+        pong.points.player2 = 1;
+        for _ in 0..29 {
+            assert!(pong.step().is_none());
+        }
+        let stats = pong.step().expect("a partida deveria terminar em 0.5s");
+        assert_eq!(stats.steps, 30);
+        assert_eq!(stats.winner, Winner::Player2);
+    }
+
+    #[test]
+    fn timed_match_tied_at_the_deadline_goes_to_the_next_point() {
+        let mut pong = still_game(1);
+        pong.rule = MatchRule::Timed(0.5);
+        freeze_ball(&mut pong);
+        for _ in 0..60 {
+            assert!(pong.step().is_none());
+        }
+        pong.ball.position.x = pong.position.x + pong.window.width;
+        let stats = pong.step().expect("o ponto deveria decidir a partida");
+        assert_eq!(stats.winner, Winner::Player1);
+        assert_eq!(stats.steps, 61);
+    }
+
+    #[test]
+    fn one_second_is_the_same_at_any_frame_rate() {
+        let mut at_60 = bot_game(5);
+        let mut at_144 = bot_game(5);
+        for _ in 0..60 {
+            at_60.update(1.0 / 60.0);
+        }
+        for _ in 0..144 {
+            at_144.update(1.0 / 144.0);
+        }
+
+        assert_eq!(at_60.steps, at_144.steps);
+        let close = |a: Vec2, b: Vec2| a.distance(b) < 1e-3;
+        assert!(close(at_60.ball.position, at_144.ball.position));
+        assert!(close(at_60.ball.velocity, at_144.ball.velocity));
+        assert!(close(at_60.player1.position, at_144.player1.position));
+        assert!(close(at_60.player2.position, at_144.player2.position));
+    }
+
+    #[test]
+    fn a_long_frame_runs_at_most_max_steps_per_frame() {
+        let mut pong = bot_game(5);
+        pong.update(1.0);
+        assert_eq!(pong.steps, MAX_STEPS_PER_FRAME as usize);
+        // O atraso que sobrou é descartado, não recuperado no quadro seguinte
+        pong.update(FIXED_DT);
+        assert_eq!(pong.steps, MAX_STEPS_PER_FRAME as usize + 1);
+    }
+}
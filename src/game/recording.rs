@@ -0,0 +1,121 @@
+//! Gravação de partidas: registra, a cada passo, o que um controlador viu e o que decidiu.
+//!
+//! O arquivo de dados é JSON Lines (um `RecordedFrame` por linha), então várias sessões
+//! podem ser acrescentadas ao mesmo arquivo.
+
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::sync::{Arc, Mutex};
+
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::game::controller::{Controller, FieldView, PlayerDirection};
+
+/// Um passo gravado: os inputs que o controlador recebeu e a decisão que tomou.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub ball_position: (f32, f32),
+    /// Velocidade da bola em unidades por segundo, como o controlador a recebe.
+    pub ball_velocity: (f32, f32),
+    pub player_position: (f32, f32),
+    pub direction: PlayerDirection,
+    pub speed: f64,
+}
+
+/// Passos gravados, compartilhados entre o controlador (dentro do `Pong`) e quem os salva.
+pub type SharedRecording = Arc<Mutex<Vec<RecordedFrame>>>;
+
+/// Envolve um controlador e grava cada decisão dele em `frames`.
+pub struct RecordingController<C: Controller> {
+    inner: C,
+    frames: SharedRecording,
+}
+
+impl<C: Controller> RecordingController<C> {
+    pub fn new(inner: C, frames: SharedRecording) -> Self {
+        RecordingController { inner, frames }
+    }
+}
+
+impl<C: Controller> Controller for RecordingController<C> {
+    fn get_input(
+        &mut self,
+        ball_position: Vec2,
+        ball_velocity: Vec2,
+        player_position: Vec2,
+    ) -> (PlayerDirection, f64) {
+        let (direction, speed) = self
+            .inner
+            .get_input(ball_position, ball_velocity, player_position);
+        self.frames.lock().unwrap().push(RecordedFrame {
+            ball_position: (ball_position.x, ball_position.y),
+            ball_velocity: (ball_velocity.x, ball_velocity.y),
+            player_position: (player_position.x, player_position.y),
+            direction,
+            speed,
+        });
+        (direction, speed)
+    }
+
+    fn observe_field(&mut self, field: &FieldView) {
+        self.inner.observe_field(field);
+    }
+}
+
+/// Acrescenta `frames` ao final do arquivo `path`, criando-o se não existir.
+pub fn append_frames(path: &str, frames: &[RecordedFrame]) -> Result<(), Box<dyn Error>> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    for frame in frames {
+        serde_json::to_writer(&mut writer, frame)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Lê todos os passos gravados em `path`, ignorando linhas em branco.
+pub fn load_frames(path: &str) -> Result<Vec<RecordedFrame>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let frame = serde_json::from_str(&line).map_err(|e| format!("{}:{}: {}", path, i + 1, e))?;
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appended_frames_load_back_in_order() {
+        let path = std::env::temp_dir().join(format!("game_quad_recording_{}.jsonl", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let frame = |i: f32, direction| RecordedFrame {
+            ball_position: (i, 2.0 * i),
+            ball_velocity: (-480.0, i),
+            player_position: (100.0, i + 0.5),
+            direction,
+            speed: f64::from(i) / 10.0,
+        };
+        let first = [frame(1.0, PlayerDirection::Up), frame(2.0, PlayerDirection::None)];
+        let second = [frame(3.0, PlayerDirection::Down)];
+
+        // Duas sessões acrescentadas ao mesmo arquivo
+        append_frames(&path, &first).unwrap();
+        append_frames(&path, &second).unwrap();
+        let loaded = load_frames(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let expected: Vec<RecordedFrame> = first.iter().chain(&second).copied().collect();
+        assert_eq!(loaded.unwrap(), expected);
+    }
+}
@@ -0,0 +1,76 @@
+use macroquad::{
+    color::{Color, BLACK, PINK, RED},
+    shapes::{draw_circle, draw_rectangle, draw_rectangle_lines},
+    text::draw_text,
+};
+
+use crate::game::pong::{Ball, Pong, BALL_RADIUS};
+
+/// Camada de desenho do Pong. Apenas lê o estado de uma simulação (`Pong`),
+/// então precisa de um contexto gráfico do macroquad, mas a simulação não.
+pub struct PongDrawer {
+    field_color: Color,
+    player_color: Color,
+    ball_color: Color,
+    score_color: Color,
+}
+
+impl PongDrawer {
+    pub fn new(field_color: Color, player_color: Color, ball_color: Color, score_color: Color) -> Self {
+        PongDrawer {
+            field_color,
+            player_color,
+            ball_color,
+            score_color,
+        }
+    }
+
+    //desenha o game (player, bola, campo)
+    pub fn draw(&self, pong: &Pong) {
+        // Desenha o campo
+        draw_rectangle_lines(
+            pong.position.x,
+            pong.position.y,
+            pong.window.width,
+            pong.window.height,
+            5.0,
+            self.field_color,
+        );
+
+        // Desenha cada jogador
+        for player in [&pong.player1, &pong.player2] {
+            draw_rectangle(
+                player.position.x,
+                player.position.y,
+                pong.player_scale.x,
+                pong.player_scale.y,
+                self.player_color,
+            );
+        }
+
+        // Escreve o placar e as rebatidas
+        draw_text(
+            &format!(
+                "Pontos {} x {}  rebatidas p1: {}  p2: {}",
+                pong.points.player1, pong.points.player2, pong.hits.player1, pong.hits.player2
+            ),
+            pong.position.x + 100.0,
+            pong.position.y - 20.0, // Desenha acima do campo, por exemplo
+            30.0,
+            self.score_color,
+        );
+
+        // Desenha a bola
+        self.draw_ball(&pong.ball);
+    }
+
+    fn draw_ball(&self, ball: &Ball) {
+        draw_circle(ball.position.x, ball.position.y, BALL_RADIUS, self.ball_color);
+    }
+}
+
+impl Default for PongDrawer {
+    fn default() -> Self {
+        PongDrawer::new(BLACK, BLACK, RED, PINK)
+    }
+}
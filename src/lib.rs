@@ -0,0 +1,4 @@
+pub mod cli;
+pub mod game;
+pub mod neural_network;
+pub mod training;
@@ -1,6 +1,7 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::neural_network::neuron::{ActivationFunction, Neuron, NeuronTrait};
+use crate::neural_network::neuron::{ActivationFunction, Neuron, NeuronGradient, NeuronTrait};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Layer {
@@ -15,6 +16,19 @@ impl Layer {
         Layer { neurons }
     }
 
+    /// Cria uma camada cujos neurônios são inicializados a partir de `rng`.
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        neurons_len: usize,
+        input_len: usize,
+        activation_function: ActivationFunction,
+        rng: &mut R,
+    ) -> Self {
+        let neurons: Vec<Neuron> = (0..neurons_len)
+            .map(|_| Neuron::new_with_rng(input_len, activation_function, rng))
+            .collect();
+        Layer { neurons }
+    }
+
     pub fn feed(&mut self, input: &[f64]) {
         for neuron in &mut self.neurons {
             neuron.calculate_output(input);
@@ -25,6 +39,16 @@ impl Layer {
         self.neurons.iter().map(|neuron| neuron.get_output()).collect()
     }
 
+    /// Retropropaga `output_deltas` (um por neurônio) pela camada, acumulando os gradientes
+    /// em `gradients`. Retorna a derivada da perda em relação a cada input da camada.
+    pub fn backward(&self, input: &[f64], output_deltas: &[f64], gradients: &mut [NeuronGradient]) -> Vec<f64> {
+        let mut input_deltas = vec![0.0; input.len()];
+        for ((neuron, &delta), gradient) in self.neurons.iter().zip(output_deltas).zip(gradients) {
+            neuron.backward(input, delta, gradient, &mut input_deltas);
+        }
+        input_deltas
+    }
+
     pub fn mut_neurons(&mut self) -> &mut Vec<Neuron> {
         &mut self.neurons
     }
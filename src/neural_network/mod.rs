@@ -2,3 +2,5 @@ pub mod neuron;
 pub mod neural_network_f;
 pub mod network_drawer;
 pub mod layers;
+pub mod network_file;
+pub mod optimizer;
\ No newline at end of file
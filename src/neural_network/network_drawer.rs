@@ -8,7 +8,7 @@ use macroquad::{
     text::draw_text,
 };
 
-use crate::NeuralNetwork;
+use crate::neural_network::neural_network_f::NeuralNetwork;
 
 pub struct NetworkDrawer {
     position: Vec2,
@@ -0,0 +1,532 @@
+//! Formato binário versionado dos arquivos de rede (`best_nn.bin` e afins).
+//!
+//! Layout: `MAGIC` (4 bytes), versão (u16, little-endian), cabeçalho com a topologia
+//! e corpo com os pesos e as features de observação, os dois últimos em bincode. Só os dados
+//! persistentes da rede vão para o arquivo; saídas, posições de desenho e inputs são
+//! recalculados em tempo de execução. Na versão 1 o corpo tinha só os pesos, e a rede lida
+//! recebe as features de `legacy_observation`.
+//!
+//! Arquivos antigos, que eram a `NeuralNetwork` serializada direto com bincode,
+//! não têm o `MAGIC` e são convertidos por `migrate_legacy`.
+//!
+//! Há também uma versão em JSON (`.json`), legível e editável à mão, com os mesmos dados:
+//! `{ "version", "topology", "observation", "layers": [{ "neurons": [{ "weights", "bias", "activation_function" }] }] }`.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::observation::ObservationConfig;
+use crate::neural_network::layers::Layer;
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::neural_network::neuron::{ActivationFunction, Neuron, NeuronTrait};
+
+/// Assinatura no início de todo arquivo de rede.
+pub const MAGIC: [u8; 4] = *b"PNGN";
+/// Versão do formato gravada por `write_network`.
+pub const FORMAT_VERSION: u16 = 2;
+
+/// Erros ao ler ou gravar um arquivo de rede.
+#[derive(Debug)]
+pub enum NetworkFileError {
+    Io(io::Error),
+    /// O conteúdo não pôde ser (de)serializado: arquivo corrompido ou truncado.
+    Encoding(bincode::Error),
+    /// O JSON não pôde ser (de)serializado ou não tem o layout esperado.
+    Json(serde_json::Error),
+    /// O arquivo foi gravado por uma versão mais nova do formato.
+    UnsupportedVersion(u16),
+    /// O corpo do arquivo não bate com a topologia declarada no cabeçalho.
+    TopologyMismatch { header: Topology, body: Topology },
+    /// Pesos incoerentes com a camada anterior (ex.: neurônio com quantidade errada de pesos).
+    InvalidNetwork(String),
+}
+
+impl fmt::Display for NetworkFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkFileError::Io(e) => write!(f, "erro de E/S: {}", e),
+            NetworkFileError::Encoding(e) => write!(f, "arquivo de rede inválido: {}", e),
+            NetworkFileError::Json(e) => write!(f, "JSON de rede inválido: {}", e),
+            NetworkFileError::UnsupportedVersion(version) => write!(
+                f,
+                "versão {} do formato não suportada (suportada até {})",
+                version, FORMAT_VERSION
+            ),
+            NetworkFileError::TopologyMismatch { header, body } => write!(
+                f,
+                "topologia do cabeçalho ({:?}) difere da rede gravada ({:?})",
+                header, body
+            ),
+            NetworkFileError::InvalidNetwork(reason) => write!(f, "rede inválida: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for NetworkFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetworkFileError::Io(e) => Some(e),
+            NetworkFileError::Encoding(e) => Some(e),
+            NetworkFileError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetworkFileError {
+    fn from(e: io::Error) -> Self {
+        NetworkFileError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for NetworkFileError {
+    fn from(e: bincode::Error) -> Self {
+        NetworkFileError::Encoding(e)
+    }
+}
+
+impl From<serde_json::Error> for NetworkFileError {
+    fn from(e: serde_json::Error) -> Self {
+        NetworkFileError::Json(e)
+    }
+}
+
+/// Formatos em que uma rede pode ser gravada.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkFormat {
+    /// Formato binário versionado (padrão).
+    Binary,
+    /// JSON legível, para comparar redes em um diff ou editar pesos à mão.
+    Json,
+}
+
+impl NetworkFormat {
+    /// Escolhe o formato pela extensão do arquivo: `.json` é JSON, o resto é binário.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => NetworkFormat::Json,
+            _ => NetworkFormat::Binary,
+        }
+    }
+}
+
+/// Topologia declarada no cabeçalho: tamanho da entrada e neurônios por camada.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Topology {
+    pub input_size: usize,
+    pub layer_sizes: Vec<usize>,
+}
+
+impl Topology {
+    pub fn of(nn: &NeuralNetwork) -> Self {
+        Topology {
+            input_size: nn.input_size(),
+            layer_sizes: nn.layers.iter().map(|layer| layer.neurons.len()).collect(),
+        }
+    }
+}
+
+/// Grava `nn` no formato atual.
+pub fn write_network<W: Write>(nn: &NeuralNetwork, mut writer: W) -> Result<(), NetworkFileError> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, &Topology::of(nn))?;
+    bincode::serialize_into(&mut writer, nn)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Lê uma rede de qualquer versão conhecida do formato, incluindo os arquivos antigos sem cabeçalho.
+pub fn read_network<R: Read>(mut reader: R) -> Result<NeuralNetwork, NetworkFileError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    let nn = match bytes.strip_prefix(&MAGIC) {
+        Some(rest) => read_versioned(rest)?,
+        None => migrate_legacy(&bytes)?,
+    };
+    validate(&nn)?;
+    Ok(nn.with_fresh_inputs())
+}
+
+/// Grava `nn` em `format`.
+pub fn write_network_as<W: Write>(
+    nn: &NeuralNetwork,
+    writer: W,
+    format: NetworkFormat,
+) -> Result<(), NetworkFileError> {
+    match format {
+        NetworkFormat::Binary => write_network(nn, writer),
+        NetworkFormat::Json => write_network_json(nn, writer),
+    }
+}
+
+/// Lê uma rede gravada em `format`.
+pub fn read_network_as<R: Read>(reader: R, format: NetworkFormat) -> Result<NeuralNetwork, NetworkFileError> {
+    match format {
+        NetworkFormat::Binary => read_network(reader),
+        NetworkFormat::Json => read_network_json(reader),
+    }
+}
+
+/// Documento JSON de uma rede. A ordem dos campos é fixa, para que diffs entre
+/// duas redes mostrem só os valores que mudaram.
+#[derive(Serialize)]
+struct JsonDocumentRef<'a> {
+    version: u16,
+    topology: Topology,
+    observation: &'a ObservationConfig,
+    layers: &'a [Layer],
+}
+
+#[derive(Deserialize)]
+struct JsonDocument {
+    version: u16,
+    topology: Option<Topology>,
+    observation: Option<ObservationConfig>,
+    layers: Vec<Layer>,
+}
+
+/// Grava `nn` como JSON indentado.
+pub fn write_network_json<W: Write>(nn: &NeuralNetwork, mut writer: W) -> Result<(), NetworkFileError> {
+    let document = JsonDocumentRef {
+        version: FORMAT_VERSION,
+        topology: Topology::of(nn),
+        observation: &nn.observation,
+        layers: &nn.layers,
+    };
+    serde_json::to_writer_pretty(&mut writer, &document)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Lê uma rede em JSON. A `topology` é opcional, o que facilita escrever uma rede à mão;
+/// se estiver presente, precisa bater com as camadas. Sem `observation`, valem as features de
+/// `legacy_observation`.
+pub fn read_network_json<R: Read>(reader: R) -> Result<NeuralNetwork, NetworkFileError> {
+    let document: JsonDocument = serde_json::from_reader(reader)?;
+    if document.version == 0 || document.version > FORMAT_VERSION {
+        return Err(NetworkFileError::UnsupportedVersion(document.version));
+    }
+
+    let nn = NeuralNetwork::from_layers(document.layers);
+    let observation = match document.observation {
+        Some(observation) => observation,
+        None => legacy_observation(nn.input_size())?,
+    };
+    let nn = nn.with_observation(observation);
+    if let Some(header) = document.topology {
+        let body = Topology::of(&nn);
+        if header != body {
+            return Err(NetworkFileError::TopologyMismatch { header, body });
+        }
+    }
+    validate(&nn)?;
+    Ok(nn)
+}
+
+fn read_versioned(bytes: &[u8]) -> Result<NeuralNetwork, NetworkFileError> {
+    let (version, mut rest) = match bytes {
+        [low, high, rest @ ..] => (u16::from_le_bytes([*low, *high]), rest),
+        _ => {
+            return Err(NetworkFileError::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "arquivo termina antes da versão",
+            )))
+        }
+    };
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(NetworkFileError::UnsupportedVersion(version));
+    }
+
+    let header: Topology = bincode::deserialize_from(&mut rest)?;
+    let nn = match version {
+        1 => {
+            let nn = NeuralNetwork::from_layers(bincode::deserialize_from(&mut rest)?);
+            let observation = legacy_observation(nn.input_size())?;
+            nn.with_observation(observation)
+        }
+        _ => bincode::deserialize_from(&mut rest)?,
+    };
+    let body = Topology::of(&nn);
+    if header != body {
+        return Err(NetworkFileError::TopologyMismatch { header, body });
+    }
+    Ok(nn)
+}
+
+/// Layout dos arquivos antigos: a `NeuralNetwork` inteira, com os campos transitórios.
+#[derive(Deserialize)]
+struct LegacyNetwork {
+    inputs: Vec<f64>,
+    layers: Vec<LegacyLayer>,
+}
+
+#[derive(Deserialize)]
+struct LegacyLayer {
+    neurons: Vec<LegacyNeuron>,
+}
+
+#[derive(Deserialize)]
+struct LegacyNeuron {
+    weights: Vec<f64>,
+    bias: f64,
+    // Transitórios: lidos apenas para avançar no arquivo
+    _output: f64,
+    activation_function: ActivationFunction,
+    _position: (f32, f32),
+}
+
+/// Converte um arquivo antigo (bincode cru, sem cabeçalho) para a rede atual.
+fn migrate_legacy(bytes: &[u8]) -> Result<NeuralNetwork, NetworkFileError> {
+    let legacy: LegacyNetwork = bincode::deserialize(bytes)?;
+    let layers = legacy
+        .layers
+        .into_iter()
+        .map(|layer| Layer {
+            neurons: layer
+                .neurons
+                .into_iter()
+                .map(|n| Neuron::new_seted(n.weights, n.bias, n.activation_function))
+                .collect(),
+        })
+        .collect();
+
+    let nn = NeuralNetwork::from_layers(layers);
+    let observation = legacy_observation(nn.input_size())?;
+    let nn = nn.with_observation(observation);
+    if nn.input_size() != legacy.inputs.len() {
+        return Err(NetworkFileError::InvalidNetwork(format!(
+            "o arquivo declara {} inputs, mas a primeira camada espera {}",
+            legacy.inputs.len(),
+            nn.input_size()
+        )));
+    }
+    Ok(nn)
+}
+
+/// Features de uma rede gravada antes de o arquivo guardá-las. O único layout conhecido é o
+/// dos 4 inputs do controlador original (`network_inputs`); com outra quantidade não há como
+/// saber o que cada input significava, e a rede é recusada.
+fn legacy_observation(input_size: usize) -> Result<ObservationConfig, NetworkFileError> {
+    if input_size != ObservationConfig::default().size() {
+        return Err(NetworkFileError::InvalidNetwork(format!(
+            "rede sem features de observação gravadas e com {} inputs; só redes de 4 inputs têm um layout conhecido",
+            input_size
+        )));
+    }
+    Ok(ObservationConfig::default())
+}
+
+/// Confere se cada neurônio tem um peso por saída da camada anterior e se as features
+/// de observação preenchem a entrada.
+fn validate(nn: &NeuralNetwork) -> Result<(), NetworkFileError> {
+    if nn.layers.is_empty() {
+        return Err(NetworkFileError::InvalidNetwork("a rede não possui camadas".into()));
+    }
+    if nn.observation.size() != nn.input_size() {
+        return Err(NetworkFileError::InvalidNetwork(format!(
+            "as features de observação somam {} inputs, mas a primeira camada espera {}",
+            nn.observation.size(),
+            nn.input_size()
+        )));
+    }
+    let mut previous_size = nn.input_size();
+    for (i, layer) in nn.layers.iter().enumerate() {
+        if layer.neurons.is_empty() {
+            return Err(NetworkFileError::InvalidNetwork(format!("camada {} está vazia", i)));
+        }
+        if let Some(neuron) = layer.neurons.iter().find(|n| n.weights.len() != previous_size) {
+            return Err(NetworkFileError::InvalidNetwork(format!(
+                "camada {} tem um neurônio com {} pesos, esperado {}",
+                i,
+                neuron.weights.len(),
+                previous_size
+            )));
+        }
+        previous_size = layer.neurons.len();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::math::Vec2;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::game::controller::Controller;
+    use crate::game::observation::ObservationFeature;
+    use crate::game::pong::FIXED_DT;
+
+    fn read_shipped(name: &str) -> Result<NeuralNetwork, NetworkFileError> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(name);
+        read_network(std::fs::File::open(&path).unwrap())
+    }
+
+    #[test]
+    fn shipped_legacy_network_sees_the_original_inputs() {
+        let mut best = read_shipped("best_nn.bin").unwrap();
+        assert_eq!(best.observation, ObservationConfig::default());
+
+        // Os inputs do controlador original: distâncias absolutas e a velocidade por passo
+        let ball = Vec2::new(320.0, 150.0);
+        let velocity = Vec2::new(-480.0, 90.0);
+        let player = Vec2::new(110.0, 260.0);
+        let original = [210.0, 110.0, (velocity.x * FIXED_DT) as f64, (velocity.y * FIXED_DT) as f64];
+        let mut expected = best.clone();
+        expected.feed(&original);
+
+        best.get_input(ball, velocity, player);
+        assert_eq!(best.get_output().unwrap(), expected.get_output().unwrap());
+    }
+
+    #[test]
+    fn rejects_legacy_files_with_an_unknown_input_layout() {
+        assert!(matches!(
+            read_shipped("rede_neural.bin"),
+            Err(NetworkFileError::InvalidNetwork(_))
+        ));
+    }
+
+    fn sample_network() -> NeuralNetwork {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        NeuralNetwork::new_with_rng(
+            4,
+            &[5, 3],
+            &[ActivationFunction::Relu, ActivationFunction::Sigmoid],
+            &mut rng,
+        )
+        .unwrap()
+    }
+
+    /// Pesos, bias e ativações de todos os neurônios, para comparar duas redes.
+    fn parameters(nn: &NeuralNetwork) -> Vec<(Vec<f64>, f64, ActivationFunction)> {
+        nn.layers
+            .iter()
+            .flat_map(|layer| &layer.neurons)
+            .map(|n| (n.weights.clone(), n.bias, n.activation_function))
+            .collect()
+    }
+
+    fn binary(nn: &NeuralNetwork) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_network(nn, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let nn = sample_network();
+        let read = read_network(binary(&nn).as_slice()).unwrap();
+        assert_eq!(parameters(&read), parameters(&nn));
+        assert_eq!(read.observation, nn.observation);
+    }
+
+    #[test]
+    fn json_round_trip_keeps_weights_and_observation() {
+        let observation = ObservationConfig::new(
+            vec![ObservationFeature::SignedOffset, ObservationFeature::BallVelocity],
+            true,
+        );
+        let nn = sample_network().with_observation(observation.clone());
+        let mut json = Vec::new();
+        write_network_json(&nn, &mut json).unwrap();
+
+        let read = read_network_json(json.as_slice()).unwrap();
+        assert_eq!(parameters(&read), parameters(&nn));
+        assert_eq!(read.observation, observation);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = binary(&sample_network());
+        bytes[..4].copy_from_slice(b"NOPE");
+        assert!(read_network(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut bytes = binary(&sample_network());
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        match read_network(bytes.as_slice()) {
+            Err(NetworkFileError::UnsupportedVersion(version)) => assert_eq!(version, FORMAT_VERSION + 1),
+            other => panic!("esperado UnsupportedVersion, veio {:?}", other.map(|_| ())),
+        }
+
+        let json = format!(r#"{{ "version": {}, "layers": [] }}"#, FORMAT_VERSION + 1);
+        assert!(matches!(
+            read_network_json(json.as_bytes()),
+            Err(NetworkFileError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_topology_mismatch() {
+        let nn = sample_network();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(FORMAT_VERSION.to_le_bytes());
+        let wrong = Topology {
+            input_size: 4,
+            layer_sizes: vec![6, 3],
+        };
+        bincode::serialize_into(&mut bytes, &wrong).unwrap();
+        bincode::serialize_into(&mut bytes, &nn).unwrap();
+        assert!(matches!(
+            read_network(bytes.as_slice()),
+            Err(NetworkFileError::TopologyMismatch { .. })
+        ));
+    }
+
+    /// Neurônio antigo: pesos, bias, saída, ativação e posição de desenho.
+    type OldNeuron = (Vec<f64>, f64, f64, ActivationFunction, (f32, f32));
+
+    /// Espelho do layout antigo, para gravar um arquivo como as versões sem cabeçalho gravavam.
+    #[derive(Serialize)]
+    struct OldNetwork {
+        inputs: Vec<f64>,
+        layers: Vec<Vec<OldNeuron>>,
+    }
+
+    #[test]
+    fn migrates_headerless_files() {
+        let nn = sample_network();
+        let old = OldNetwork {
+            inputs: vec![0.0; nn.input_size()],
+            layers: nn
+                .layers
+                .iter()
+                .map(|layer| {
+                    layer
+                        .neurons
+                        .iter()
+                        .map(|n| (n.weights.clone(), n.bias, 0.5, n.activation_function, (1.0, 2.0)))
+                        .collect()
+                })
+                .collect(),
+        };
+        let bytes = bincode::serialize(&old).unwrap();
+
+        let read = read_network(bytes.as_slice()).unwrap();
+        assert_eq!(parameters(&read), parameters(&nn));
+        assert_eq!(read.observation, ObservationConfig::default());
+    }
+
+    #[test]
+    fn migrates_version_1_files() {
+        let nn = sample_network();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(1u16.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &Topology::of(&nn)).unwrap();
+        bincode::serialize_into(&mut bytes, &nn.layers).unwrap();
+
+        let read = read_network(bytes.as_slice()).unwrap();
+        assert_eq!(parameters(&read), parameters(&nn));
+        assert_eq!(read.observation, ObservationConfig::default());
+    }
+}
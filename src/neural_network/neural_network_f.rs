@@ -1,35 +1,58 @@
-use std::cell::{Cell, RefCell};
 use std::error::Error;
 use std::fs::File;
-use std::io::Write;
-use std::rc::Rc;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::io::{BufReader, BufWriter};
+use std::sync::{Arc, Mutex};
 
 use macroquad::math::Vec2;
-use rand::distr::uniform::SampleBorrow;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::game::controller::{Controller, PlayerDirection};
+use crate::game::controller::{Controller, FieldView, PlayerDirection};
+use crate::game::observation::{ObservationConfig, PlayerView};
+use crate::game::pong::{GameWindow, FIXED_DT};
 
-use crate::neural_network::neuron::{ActivationFunction, Neuron};
+use crate::neural_network::neuron::{ActivationFunction, Neuron, NeuronTrait};
 
-use super::layers::{self, Layer};
+use super::layers::Layer;
+use super::network_file::{self, NetworkFileError, NetworkFormat};
+use super::optimizer::{Loss, NetworkGradients, Optimizer, Sample};
+
+/// Rede compartilhada entre um jogo (como controlador) e quem a desenha ou evolui.
+/// É `Send`, então jogos que a usam podem ser avaliados em outras threads.
+pub type SharedNetwork = Arc<Mutex<NeuralNetwork>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralNetwork {
-    pub inputs: Vec<f64>, 
+    /// Últimos inputs recebidos (usados só para desenho, não são salvos).
+    #[serde(skip)]
+    pub inputs: Vec<f64>,
     pub layers: Vec<Layer>,
-    #[serde(skip, default = "Instant::now")]
-    pub timer: Instant,
+    /// Features que a rede recebe como controlador; gravadas junto com os pesos.
+    #[serde(default)]
+    pub observation: ObservationConfig,
+    /// Último campo visto em `observe_field`.
+    #[serde(skip)]
+    field: Option<FieldView>,
 }
 
 impl NeuralNetwork {
     /// Cria uma rede neural com os inputs inicializados com zeros.
     pub fn new(
         input_size: usize,
-        layers_sizes: &Vec<usize>,
-        activation_functions: &Vec<ActivationFunction>,
+        layers_sizes: &[usize],
+        activation_functions: &[ActivationFunction],
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new_with_rng(input_size, layers_sizes, activation_functions, &mut rand::rng())
+    }
+
+    /// Igual a `new`, mas sorteia os pesos iniciais a partir de `rng`,
+    /// de modo que a mesma semente gera sempre a mesma rede.
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        input_size: usize,
+        layers_sizes: &[usize],
+        activation_functions: &[ActivationFunction],
+        rng: &mut R,
     ) -> Result<Self, Box<dyn Error>> {
         if layers_sizes.len() != activation_functions.len() {
             return Err("layers_sizes e activation_functions devem ter o mesmo tamanho".into());
@@ -39,16 +62,48 @@ impl NeuralNetwork {
         let mut layers = Vec::with_capacity(layers_sizes.len());
         let mut previous_size = input_size;
         for (&size, &activation) in layers_sizes.iter().zip(activation_functions.iter()) {
-            layers.push(Layer::new(size, previous_size, activation));
+            layers.push(Layer::new_with_rng(size, previous_size, activation, rng));
             previous_size = size;
         }
         Ok(NeuralNetwork {
             inputs,
             layers,
-            timer: Instant::now(),
+            observation: ObservationConfig::default(),
+            field: None,
         })
     }
 
+    /// Monta uma rede a partir de camadas prontas, com os inputs zerados.
+    pub fn from_layers(layers: Vec<Layer>) -> Self {
+        NeuralNetwork {
+            inputs: Vec::new(),
+            layers,
+            observation: ObservationConfig::default(),
+            field: None,
+        }
+        .with_fresh_inputs()
+    }
+
+    /// Troca as features que a rede recebe. O tamanho delas deve bater com `input_size`.
+    pub fn with_observation(mut self, observation: ObservationConfig) -> Self {
+        self.observation = observation;
+        self
+    }
+
+    /// Quantidade de inputs que a rede espera, deduzida da primeira camada.
+    pub fn input_size(&self) -> usize {
+        self.layers
+            .first()
+            .and_then(|layer| layer.neurons.first())
+            .map_or(0, |neuron| neuron.weights.len())
+    }
+
+    /// Zera os inputs guardados, com o tamanho que a rede espera.
+    pub fn with_fresh_inputs(mut self) -> Self {
+        self.inputs = vec![0.0; self.input_size()];
+        self
+    }
+
     pub fn from_model(model: &NeuralNetworkModel) -> Self {
         NeuralNetwork::new(
             model.input_layer_size,
@@ -80,23 +135,159 @@ impl NeuralNetwork {
         &mut self.layers
     }
 
-    pub fn save_neural_network_bin(&self, filename: &str) -> std::io::Result<()> {
-        let encoded: Vec<u8> =
-            bincode::serialize(self).expect("Falha ao serializar a rede neural.");
-        let mut file = File::create(filename)?;
-        file.write_all(&encoded)?;
-        Ok(())
+    /// Salva a rede no formato versionado de `network_file`.
+    pub fn save_neural_network_bin(&self, filename: &str) -> Result<(), NetworkFileError> {
+        let file = File::create(filename)?;
+        network_file::write_network(self, BufWriter::new(file))
+    }
+
+    /// Carrega uma rede salva em qualquer versão do formato, inclusive os arquivos antigos.
+    pub fn load_neural_network_bin(filename: &str) -> Result<NeuralNetwork, NetworkFileError> {
+        let file = File::open(filename)?;
+        network_file::read_network(BufReader::new(file))
+    }
+
+    /// Salva a rede no formato indicado pela extensão de `filename` (`.json` ou binário).
+    pub fn save_neural_network(&self, filename: &str) -> Result<(), NetworkFileError> {
+        let file = File::create(filename)?;
+        network_file::write_network_as(self, BufWriter::new(file), NetworkFormat::from_path(filename))
     }
 
-    pub fn load_neural_network_bin(filename: &str) -> std::io::Result<NeuralNetwork> {
+    /// Carrega uma rede no formato indicado pela extensão de `filename` (`.json` ou binário).
+    pub fn load_neural_network(filename: &str) -> Result<NeuralNetwork, NetworkFileError> {
         let file = File::open(filename)?;
-        let decoded: NeuralNetwork =
-            bincode::deserialize_from(file).expect("Falha ao deserializar a rede neural.");
-        Ok(decoded)
+        network_file::read_network_as(BufReader::new(file), NetworkFormat::from_path(filename))
+    }
+
+    /// Faz o feedforward de `inputs` e retropropaga a perda em relação a `targets`,
+    /// somando os gradientes em `gradients`. Retorna o valor da perda.
+    pub fn backward(
+        &mut self,
+        inputs: &[f64],
+        targets: &[f64],
+        loss: Loss,
+        gradients: &mut NetworkGradients,
+    ) -> f64 {
+        self.feed(inputs);
+        let outputs = self.get_output().unwrap();
+        assert!(
+            outputs.len() == targets.len(),
+            "Número de saídas ({}) não corresponde ao número de alvos ({})",
+            outputs.len(),
+            targets.len()
+        );
+
+        let mut deltas = loss.derivative(&outputs, targets);
+        for i in (0..self.layers.len()).rev() {
+            let layer_inputs = match i {
+                0 => inputs.to_vec(),
+                _ => self.layers[i - 1].get_outputs(),
+            };
+            deltas = self.layers[i].backward(&layer_inputs, &deltas, &mut gradients.layers[i]);
+        }
+        loss.value(&outputs, targets)
     }
 
-    pub fn print_NeuralNetworkModel(&self) {
-        self.layers[0].neurons[0].print_Neuron();
+    /// Um passo de otimização com a média dos gradientes de `batch`. Retorna a perda média do lote.
+    pub fn train_batch(&mut self, batch: &[Sample], loss: Loss, optimizer: &mut dyn Optimizer) -> f64 {
+        if batch.is_empty() {
+            return 0.0;
+        }
+        let mut gradients = NetworkGradients::zeros_like(self);
+        let total: f64 = batch
+            .iter()
+            .map(|sample| self.backward(&sample.inputs, &sample.targets, loss, &mut gradients))
+            .sum();
+        let scale = 1.0 / batch.len() as f64;
+        gradients.scale(scale);
+        optimizer.step(self, &gradients);
+        total * scale
+    }
+
+    /// Uma época de treino: embaralha `samples` com `rng` e treina em lotes de `batch_size`.
+    /// Retorna a perda média da época.
+    pub fn train_epoch<R: Rng + ?Sized>(
+        &mut self,
+        samples: &[Sample],
+        batch_size: usize,
+        loss: Loss,
+        optimizer: &mut dyn Optimizer,
+        rng: &mut R,
+    ) -> f64 {
+        let mut order: Vec<usize> = (0..samples.len()).collect();
+        order.shuffle(rng);
+
+        let mut total = 0.0;
+        for chunk in order.chunks(batch_size.max(1)) {
+            let batch: Vec<Sample> = chunk.iter().map(|&i| samples[i].clone()).collect();
+            total += self.train_batch(&batch, loss, optimizer) * batch.len() as f64;
+        }
+        total / samples.len().max(1) as f64
+    }
+
+    pub fn print_neural_network_model(&self) {
+        self.layers[0].neurons[0].print_neuron();
+    }
+
+    /// Quantidade de inputs da camada `layer`: o tamanho da entrada da rede ou da camada anterior.
+    pub fn layer_input_size(&self, layer: usize) -> usize {
+        match layer {
+            0 => self.input_size(),
+            _ => self.layers[layer - 1].neurons.len(),
+        }
+    }
+
+    /// Acrescenta um neurônio aleatório à camada oculta `layer`, com a mesma ativação dos
+    /// outros. A camada seguinte ganha um peso zerado para ele, então a saída da rede não muda.
+    pub fn add_neuron<R: Rng + ?Sized>(&mut self, layer: usize, rng: &mut R) {
+        assert!(layer + 1 < self.layers.len(), "só camadas ocultas podem ganhar neurônios");
+        let input_len = self.layer_input_size(layer);
+        let activation = self.layers[layer]
+            .neurons
+            .first()
+            .map_or(ActivationFunction::Relu, |neuron| neuron.activation_function);
+        self.layers[layer]
+            .neurons
+            .push(Neuron::new_with_rng(input_len, activation, rng));
+        for neuron in self.layers[layer + 1].neurons.iter_mut() {
+            neuron.weights.push(0.0);
+        }
+    }
+
+    /// Remove o neurônio `index` da camada oculta `layer` e os pesos que a camada seguinte tinha para ele.
+    pub fn remove_neuron(&mut self, layer: usize, index: usize) {
+        assert!(layer + 1 < self.layers.len(), "só camadas ocultas podem perder neurônios");
+        assert!(self.layers[layer].neurons.len() > 1, "a camada precisa manter ao menos um neurônio");
+        self.layers[layer].neurons.remove(index);
+        for neuron in self.layers[layer + 1].neurons.iter_mut() {
+            neuron.weights.remove(index);
+        }
+    }
+
+    /// Insere antes da camada `index` uma camada linear identidade (cada neurônio repassa um
+    /// input). A saída da rede não muda; a nova camada evolui a partir daí.
+    pub fn add_identity_layer(&mut self, index: usize) {
+        assert!(index < self.layers.len(), "a camada nova precisa vir antes da camada de saída");
+        let size = self.layer_input_size(index);
+        let neurons = (0..size)
+            .map(|i| {
+                let mut weights = vec![0.0; size];
+                weights[i] = 1.0;
+                Neuron::new_seted(weights, 0.0, ActivationFunction::Linear)
+            })
+            .collect();
+        self.layers.insert(index, Layer { neurons });
+    }
+
+    /// Remove a camada oculta `index`. Os neurônios da camada seguinte têm os pesos cortados
+    /// ou completados com zeros para casar com o novo tamanho da entrada.
+    pub fn remove_layer(&mut self, index: usize) {
+        assert!(index + 1 < self.layers.len(), "a camada de saída não pode ser removida");
+        let input_len = self.layer_input_size(index);
+        self.layers.remove(index);
+        for neuron in self.layers[index].neurons.iter_mut() {
+            neuron.weights.resize(input_len, 0.0);
+        }
     }
 
     pub fn all_weights_mut(&mut self) -> Vec<&mut f64> {
@@ -114,7 +305,7 @@ impl NeuralNetwork {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralNetworkModel {
     pub input_layer_size: usize,
     pub hidden_layers_sizes: Vec<usize>,
@@ -143,6 +334,17 @@ impl NeuralNetworkModel {
         .unwrap()
     }
 
+    /// Gera uma rede com este modelo usando `rng` para os pesos iniciais.
+    pub fn generate_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> NeuralNetwork {
+        NeuralNetwork::new_with_rng(
+            self.input_layer_size,
+            &self.hidden_layers_sizes,
+            &self.activation_functions,
+            rng,
+        )
+        .unwrap()
+    }
+
     pub fn from_neural_network(nn: NeuralNetwork) -> Self {
         let mut hidden_layers_sizes = Vec::new();
         let mut activation_functions = Vec::new();
@@ -153,16 +355,43 @@ impl NeuralNetworkModel {
         }
 
         NeuralNetworkModel {
-            input_layer_size: nn.inputs.len(), // agora os inputs estão armazenados
+            input_layer_size: nn.input_size(),
             hidden_layers_sizes,
             activation_functions,
         }
     }
 }
 
-const REACTION_TIME: f64 = 0.16;
+/// Inputs que a rede recebe como controlador: distâncias (x, y) entre jogador e bola
+/// e a velocidade da bola.
+pub fn network_inputs(ball_position: Vec2, ball_velocity: Vec2, player_position: Vec2) -> [f64; 4] {
+    let bp = ball_position;
+    let pp = player_position;
 
-impl Controller for Rc<RefCell<NeuralNetwork>> {
+    let distance_ball_player_x = f32::max(pp.x, bp.x) - f32::min(pp.x, bp.x);
+    let distance_ball_player_y = f32::max(pp.y, bp.y) - f32::min(pp.y, bp.y);
+    // A rede enxerga a velocidade em unidades por passo, como foi treinada
+    let ball_velocity = ball_velocity * FIXED_DT;
+
+    [
+        distance_ball_player_x as f64,
+        distance_ball_player_y as f64,
+        ball_velocity.x as f64,
+        ball_velocity.y as f64,
+    ]
+}
+
+/// Saída que a rede deveria produzir para tomar a decisão (`direction`, `speed`).
+/// É o inverso do mapeamento usado em `get_input`: `[sobe, desce, velocidade]`.
+pub fn output_targets(direction: PlayerDirection, speed: f64) -> [f64; 3] {
+    match direction {
+        PlayerDirection::Up => [1.0, 0.0, speed],
+        PlayerDirection::Down => [0.0, 1.0, speed],
+        PlayerDirection::None => [0.0, 0.0, speed],
+    }
+}
+
+impl Controller for NeuralNetwork {
     fn get_input(
         &mut self,
         ball_position: Vec2,
@@ -172,31 +401,60 @@ impl Controller for Rc<RefCell<NeuralNetwork>> {
         // returns Direction and Speed of the movement
         // speed must be between 0 and 1, it will be multiplied by the player speed
 
-        // Você precisa decidir como extrair os valores (ou usar unwrap) se necessário.
-        let bp = ball_position;
-        let pp = player_position;
+        // Fora de uma partida não há campo; as features padrão não dependem dele
+        let field = self.field.unwrap_or(FieldView {
+            opponent_position: Vec2::ZERO,
+            position: Vec2::ZERO,
+            window: GameWindow::new(1.0, 1.0),
+        });
+        let view = PlayerView {
+            ball_position,
+            ball_velocity,
+            player_position,
+            field,
+        };
+        let inputs = self.observation.build(&view);
+        self.feed(&inputs);
+        output_action(&self.get_output().unwrap())
+    }
 
-        let distance_ball_player_x = f32::max(pp.x, bp.x) - f32::min(pp.x, bp.x);
-        let distance_ball_player_y = f32::max(pp.y, bp.y) - f32::min(pp.y, bp.y);
+    fn observe_field(&mut self, field: &FieldView) {
+        self.field = Some(*field);
+    }
+}
 
-        self.borrow_mut().feed(&[
-            distance_ball_player_x as f64,
-            distance_ball_player_y as f64,
-            ball_velocity.x as f64,
-            ball_velocity.y as f64,
-        ]);
+/// Saídas que a rede precisa ter para servir de controlador: `[sobe, desce, velocidade]`.
+pub const CONTROLLER_OUTPUTS: usize = 3;
 
-        let output = self.borrow().get_output().unwrap();
+/// Converte as saídas da rede em direção e velocidade: [0] acima de 0,5 sobe, [1] desce
+/// (os dois juntos param) e [2] é a velocidade. Saídas que faltarem contam como 0.
+pub fn output_action(output: &[f64]) -> (PlayerDirection, f64) {
+    let [up, down, speed] = [0, 1, 2].map(|i| output.get(i).copied().unwrap_or(0.0));
+    let mut direcition = PlayerDirection::None;
+    if up > 0.5 && down > 0.5 {
+        direcition = PlayerDirection::None;
+    } else if up > 0.5 {
+        direcition = PlayerDirection::Up;
+    } else if down > 0.5 {
+        direcition = PlayerDirection::Down;
+    }
 
-        let mut direcition = PlayerDirection::None;
-        if output[0] > 0.5 && output[1] > 0.5 {
-            direcition = PlayerDirection::None;
-        } else if output[0] > 0.5 {
-            direcition = PlayerDirection::Up;
-        } else if output[1] > 0.5 {
-            direcition = PlayerDirection::Down;
-        }
+    (direcition, speed)
+}
+
+impl Controller for SharedNetwork {
+    fn get_input(
+        &mut self,
+        ball_position: Vec2,
+        ball_velocity: Vec2,
+        player_position: Vec2,
+    ) -> (PlayerDirection, f64) {
+        self.lock()
+            .unwrap()
+            .get_input(ball_position, ball_velocity, player_position)
+    }
 
-        (direcition, output[2])
+    fn observe_field(&mut self, field: &FieldView) {
+        self.lock().unwrap().observe_field(field);
     }
 }
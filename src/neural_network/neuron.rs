@@ -1,10 +1,12 @@
 // neuron.rs
 
-use rand::{random_range, Rng};
+use std::str::FromStr;
+
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 /// Define as funções de ativação disponíveis para o neurônio.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ActivationFunction {
     Sigmoid,
     Relu,
@@ -12,13 +14,75 @@ pub enum ActivationFunction {
     Linear,
 }
 
+impl FromStr for ActivationFunction {
+    type Err = String;
+
+    /// Aceita o nome da função sem diferenciar maiúsculas (`relu`, `Sigmoid`, ...).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sigmoid" => Ok(ActivationFunction::Sigmoid),
+            "relu" => Ok(ActivationFunction::Relu),
+            "tanh" => Ok(ActivationFunction::Tanh),
+            "linear" => Ok(ActivationFunction::Linear),
+            _ => Err(format!("função de ativação desconhecida: {}", s)),
+        }
+    }
+}
+
+impl ActivationFunction {
+    /// Aplica a função de ativação à soma ponderada `x`.
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            ActivationFunction::Sigmoid => sigmoid(x),
+            ActivationFunction::Relu => relu(x),
+            ActivationFunction::Tanh => tanh(x),
+            ActivationFunction::Linear => linear(x),
+        }
+    }
+
+    /// Derivada da ativação, escrita em função da saída `y = f(x)` já calculada
+    /// (é o que o neurônio guarda depois do feedforward).
+    pub fn derivative(self, output: f64) -> f64 {
+        match self {
+            ActivationFunction::Sigmoid => output * (1.0 - output),
+            ActivationFunction::Relu => {
+                if output > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ActivationFunction::Tanh => 1.0 - output * output,
+            ActivationFunction::Linear => 1.0,
+        }
+    }
+}
+
+/// Gradiente da perda em relação aos parâmetros de um neurônio.
+#[derive(Debug, Clone, Default)]
+pub struct NeuronGradient {
+    pub weights: Vec<f64>,
+    pub bias: f64,
+}
+
+impl NeuronGradient {
+    pub fn zeros(input_len: usize) -> Self {
+        NeuronGradient {
+            weights: vec![0.0; input_len],
+            bias: 0.0,
+        }
+    }
+}
+
 /// Estrutura que representa um único neurônio.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Neuron {
     pub weights: Vec<f64>,
     pub bias: f64,
+    #[serde(skip)]
     pub output: f64,
     pub activation_function: ActivationFunction,
+    #[serde(skip)]
     pub position: (f32, f32), // adaptaão pra desenho
 }
 
@@ -62,17 +126,7 @@ pub trait NeuronTrait {
 
 impl NeuronTrait for Neuron {
     fn new(input_len: usize, activation_function: ActivationFunction) -> Self {
-
-        let weights: Vec<f64> = (0..input_len).map(|_| random_range(-1.0..=1.0)).collect();
-        let bias: f64 = random_range(-1.0..=1.0);
-
-        Neuron {
-            weights,
-            bias,
-            output: 0.0,
-            activation_function,
-            position: (0.0, 0.0),
-        }
+        Neuron::new_with_rng(input_len, activation_function, &mut rand::rng())
     }
 
     fn new_clone(neuron: &Neuron) -> Self {
@@ -111,12 +165,7 @@ impl NeuronTrait for Neuron {
             + self.bias;
 
         // Aplica a função de ativação escolhida.
-        self.output = match self.activation_function {
-            ActivationFunction::Sigmoid => sigmoid(sum),
-            ActivationFunction::Relu => relu(sum),
-            ActivationFunction::Tanh => tanh(sum),
-            ActivationFunction::Linear => linear(sum),
-        };
+        self.output = self.activation_function.apply(sum);
     }
 
     fn get_weights(&self) -> &[f64] {
@@ -152,6 +201,42 @@ impl NeuronTrait for Neuron {
 
 
 impl Neuron {
+    /// Cria um neurônio com pesos e bias sorteados a partir de `rng`.
+    pub fn new_with_rng<R: Rng + ?Sized>(
+        input_len: usize,
+        activation_function: ActivationFunction,
+        rng: &mut R,
+    ) -> Self {
+        let weights: Vec<f64> = (0..input_len).map(|_| rng.random_range(-1.0..=1.0)).collect();
+        let bias: f64 = rng.random_range(-1.0..=1.0);
+
+        Neuron {
+            weights,
+            bias,
+            output: 0.0,
+            activation_function,
+            position: (0.0, 0.0),
+        }
+    }
+
+    /// Passo de retropropagação do neurônio, usando a saída do último `calculate_output(inputs)`.
+    /// `output_delta` é a derivada da perda em relação à saída do neurônio; acumula o gradiente
+    /// dos parâmetros em `gradient` e a derivada em relação a cada input em `input_deltas`.
+    pub fn backward(
+        &self,
+        inputs: &[f64],
+        output_delta: f64,
+        gradient: &mut NeuronGradient,
+        input_deltas: &mut [f64],
+    ) {
+        let delta = output_delta * self.activation_function.derivative(self.output);
+        for (i, (&input, &weight)) in inputs.iter().zip(self.weights.iter()).enumerate() {
+            gradient.weights[i] += delta * input;
+            input_deltas[i] += delta * weight;
+        }
+        gradient.bias += delta;
+    }
+
     pub fn mutate_weight(&mut self, index: usize, delta: f64) {
 
         if let Some(weight) = self.weights.get_mut(index) {
@@ -168,7 +253,7 @@ impl Neuron {
 
     }
 
-    pub fn print_Neuron(&self) {
+    pub fn print_neuron(&self) {
 
         println!("Neuron: {:?}", self);
 
@@ -0,0 +1,347 @@
+//! Treino supervisionado: funções de perda, gradientes da rede e otimizadores (SGD e Adam).
+//!
+//! Os parâmetros são percorridos sempre na mesma ordem de `NeuralNetwork::all_weights_mut`
+//! (pesos e depois bias de cada neurônio, camada por camada).
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::neural_network::neuron::NeuronGradient;
+
+/// Um exemplo de treino: inputs da rede e a saída esperada.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+    pub inputs: Vec<f64>,
+    pub targets: Vec<f64>,
+}
+
+/// Funções de perda disponíveis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Loss {
+    /// Erro quadrático médio.
+    MeanSquaredError,
+    /// Entropia cruzada binária, para saídas em (0, 1) como as da sigmoid.
+    BinaryCrossEntropy,
+}
+
+impl FromStr for Loss {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mse" => Ok(Loss::MeanSquaredError),
+            "bce" => Ok(Loss::BinaryCrossEntropy),
+            _ => Err(format!("função de perda desconhecida: {} (use mse ou bce)", s)),
+        }
+    }
+}
+
+/// Evita `ln(0)` e divisões por zero na entropia cruzada.
+const PROBABILITY_EPSILON: f64 = 1e-7;
+
+impl Loss {
+    /// Valor da perda, na média das saídas.
+    pub fn value(self, outputs: &[f64], targets: &[f64]) -> f64 {
+        let n = outputs.len().max(1) as f64;
+        let total: f64 = outputs
+            .iter()
+            .zip(targets)
+            .map(|(&y, &t)| match self {
+                Loss::MeanSquaredError => (y - t).powi(2),
+                Loss::BinaryCrossEntropy => {
+                    let y = y.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    -(t * y.ln() + (1.0 - t) * (1.0 - y).ln())
+                }
+            })
+            .sum();
+        total / n
+    }
+
+    /// Derivada da perda em relação a cada saída.
+    pub fn derivative(self, outputs: &[f64], targets: &[f64]) -> Vec<f64> {
+        let n = outputs.len().max(1) as f64;
+        outputs
+            .iter()
+            .zip(targets)
+            .map(|(&y, &t)| match self {
+                Loss::MeanSquaredError => 2.0 * (y - t) / n,
+                Loss::BinaryCrossEntropy => {
+                    let y = y.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+                    (y - t) / (y * (1.0 - y)) / n
+                }
+            })
+            .collect()
+    }
+}
+
+/// Gradiente da perda em relação a todos os parâmetros da rede, com o mesmo formato dela.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkGradients {
+    pub layers: Vec<Vec<NeuronGradient>>,
+}
+
+impl NetworkGradients {
+    /// Gradientes zerados com o formato de `nn`.
+    pub fn zeros_like(nn: &NeuralNetwork) -> Self {
+        NetworkGradients {
+            layers: nn
+                .layers
+                .iter()
+                .map(|layer| {
+                    layer
+                        .neurons
+                        .iter()
+                        .map(|neuron| NeuronGradient::zeros(neuron.weights.len()))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Multiplica todos os gradientes por `factor` (ex.: para tirar a média de um lote).
+    pub fn scale(&mut self, factor: f64) {
+        for gradient in self.layers.iter_mut().flatten() {
+            gradient.weights.iter_mut().for_each(|g| *g *= factor);
+            gradient.bias *= factor;
+        }
+    }
+
+    /// Soma `other` multiplicado por `factor` a estes gradientes.
+    pub fn add_scaled(&mut self, other: &NetworkGradients, factor: f64) {
+        for (gradient, other) in self.layers.iter_mut().flatten().zip(other.layers.iter().flatten()) {
+            for (g, o) in gradient.weights.iter_mut().zip(&other.weights) {
+                *g += factor * o;
+            }
+            gradient.bias += factor * other.bias;
+        }
+    }
+
+    /// Gradientes na mesma ordem de `NeuralNetwork::all_weights_mut`.
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.layers
+            .iter()
+            .flatten()
+            .flat_map(|gradient| gradient.weights.iter().copied().chain(std::iter::once(gradient.bias)))
+    }
+}
+
+/// Regra de atualização dos parâmetros a partir dos gradientes.
+pub trait Optimizer {
+    /// Aplica um passo de descida de gradiente em `nn`.
+    fn step(&mut self, nn: &mut NeuralNetwork, gradients: &NetworkGradients);
+}
+
+/// Otimizadores selecionáveis por configuração.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptimizerKind {
+    Sgd,
+    Adam,
+}
+
+impl OptimizerKind {
+    /// Cria o otimizador com a taxa de aprendizado `learning_rate` (e `momentum`, no SGD).
+    pub fn build(self, learning_rate: f64, momentum: f64) -> Box<dyn Optimizer> {
+        match self {
+            OptimizerKind::Sgd => Box::new(Sgd::new(learning_rate, momentum)),
+            OptimizerKind::Adam => Box::new(Adam::new(learning_rate)),
+        }
+    }
+}
+
+impl FromStr for OptimizerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sgd" => Ok(OptimizerKind::Sgd),
+            "adam" => Ok(OptimizerKind::Adam),
+            _ => Err(format!("otimizador desconhecido: {} (use sgd ou adam)", s)),
+        }
+    }
+}
+
+/// Descida de gradiente estocástica, com momento opcional.
+#[derive(Debug, Clone)]
+pub struct Sgd {
+    pub learning_rate: f64,
+    /// Fração da velocidade anterior mantida a cada passo; 0 é o SGD puro.
+    pub momentum: f64,
+    velocity: Vec<f64>,
+}
+
+impl Sgd {
+    pub fn new(learning_rate: f64, momentum: f64) -> Self {
+        Sgd {
+            learning_rate,
+            momentum,
+            velocity: Vec::new(),
+        }
+    }
+}
+
+impl Optimizer for Sgd {
+    fn step(&mut self, nn: &mut NeuralNetwork, gradients: &NetworkGradients) {
+        let params = nn.all_weights_mut();
+        self.velocity.resize(params.len(), 0.0);
+        for ((param, gradient), velocity) in params.into_iter().zip(gradients.values()).zip(&mut self.velocity) {
+            *velocity = self.momentum * *velocity - self.learning_rate * gradient;
+            *param += *velocity;
+        }
+    }
+}
+
+/// Otimizador Adam (Kingma & Ba, 2015).
+#[derive(Debug, Clone)]
+pub struct Adam {
+    pub learning_rate: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub epsilon: f64,
+    /// Média móvel dos gradientes.
+    m: Vec<f64>,
+    /// Média móvel dos gradientes ao quadrado.
+    v: Vec<f64>,
+    /// Passos já dados, para a correção de viés.
+    t: i32,
+}
+
+impl Adam {
+    /// Adam com os valores usuais de `beta1`, `beta2` e `epsilon`.
+    pub fn new(learning_rate: f64) -> Self {
+        Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            m: Vec::new(),
+            v: Vec::new(),
+            t: 0,
+        }
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self, nn: &mut NeuralNetwork, gradients: &NetworkGradients) {
+        let params = nn.all_weights_mut();
+        self.m.resize(params.len(), 0.0);
+        self.v.resize(params.len(), 0.0);
+        self.t += 1;
+        let m_correction = 1.0 - self.beta1.powi(self.t);
+        let v_correction = 1.0 - self.beta2.powi(self.t);
+
+        for (i, (param, gradient)) in params.into_iter().zip(gradients.values()).enumerate() {
+            self.m[i] = self.beta1 * self.m[i] + (1.0 - self.beta1) * gradient;
+            self.v[i] = self.beta2 * self.v[i] + (1.0 - self.beta2) * gradient * gradient;
+            let m_hat = self.m[i] / m_correction;
+            let v_hat = self.v[i] / v_correction;
+            *param -= self.learning_rate * m_hat / (v_hat.sqrt() + self.epsilon);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::neural_network::neuron::ActivationFunction;
+
+    fn small_network() -> NeuralNetwork {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        NeuralNetwork::new_with_rng(
+            3,
+            &[4, 2],
+            &[ActivationFunction::Tanh, ActivationFunction::Sigmoid],
+            &mut rng,
+        )
+        .unwrap()
+    }
+
+    fn loss_at(nn: &mut NeuralNetwork, sample: &Sample, loss: Loss) -> f64 {
+        nn.feed(&sample.inputs);
+        loss.value(&nn.get_output().unwrap(), &sample.targets)
+    }
+
+    /// Compara o gradiente da retropropagação com diferenças finitas centrais.
+    fn check_gradient(loss: Loss) {
+        let mut nn = small_network();
+        let sample = Sample {
+            inputs: vec![0.3, -0.7, 0.5],
+            targets: vec![1.0, 0.0],
+        };
+        let mut gradients = NetworkGradients::zeros_like(&nn);
+        nn.backward(&sample.inputs, &sample.targets, loss, &mut gradients);
+        let analytic: Vec<f64> = gradients.values().collect();
+
+        let h = 1e-6;
+        for (i, &expected) in analytic.iter().enumerate() {
+            let original = *nn.all_weights_mut()[i];
+            *nn.all_weights_mut()[i] = original + h;
+            let plus = loss_at(&mut nn, &sample, loss);
+            *nn.all_weights_mut()[i] = original - h;
+            let minus = loss_at(&mut nn, &sample, loss);
+            *nn.all_weights_mut()[i] = original;
+
+            let numeric = (plus - minus) / (2.0 * h);
+            assert!(
+                (numeric - expected).abs() < 1e-6,
+                "parâmetro {}: numérico {}, retropropagação {}",
+                i,
+                numeric,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn mse_gradient_matches_finite_differences() {
+        check_gradient(Loss::MeanSquaredError);
+    }
+
+    #[test]
+    fn bce_gradient_matches_finite_differences() {
+        check_gradient(Loss::BinaryCrossEntropy);
+    }
+
+    /// Perda média antes e depois de `steps` passos de `optimizer` num problema pequeno.
+    fn train_toy(mut optimizer: Box<dyn Optimizer>, steps: usize) -> (f64, f64) {
+        let mut nn = small_network();
+        let samples: Vec<Sample> = (0..8)
+            .map(|i| {
+                let x = i as f64 / 4.0 - 1.0;
+                Sample {
+                    inputs: vec![x, -x, 0.5 * x],
+                    targets: vec![(x > 0.0) as u8 as f64, (x <= 0.0) as u8 as f64],
+                }
+            })
+            .collect();
+        let average_loss = |nn: &mut NeuralNetwork| {
+            samples
+                .iter()
+                .map(|sample| loss_at(nn, sample, Loss::MeanSquaredError))
+                .sum::<f64>()
+                / samples.len() as f64
+        };
+
+        let before = average_loss(&mut nn);
+        for _ in 0..steps {
+            nn.train_batch(&samples, Loss::MeanSquaredError, optimizer.as_mut());
+        }
+        (before, average_loss(&mut nn))
+    }
+
+    #[test]
+    fn sgd_lowers_the_loss() {
+        let (before, after) = train_toy(OptimizerKind::Sgd.build(0.5, 0.9), 300);
+        assert!(after < before / 2.0, "perda foi de {} para {}", before, after);
+    }
+
+    #[test]
+    fn adam_lowers_the_loss() {
+        let (before, after) = train_toy(OptimizerKind::Adam.build(0.05, 0.0), 300);
+        assert!(after < before / 2.0, "perda foi de {} para {}", before, after);
+    }
+}
@@ -0,0 +1,181 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::sync::{Arc, Mutex};
+
+use macroquad::math::vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::training::config::TrainingConfig;
+use crate::training::es::EsState;
+use crate::training::league::League;
+use crate::training::population::{GamePack, GenerationStats, TrainingState};
+use crate::training::TrainingRng;
+
+/// Assinatura no início de todo checkpoint.
+const MAGIC: [u8; 4] = *b"PNGC";
+/// Versão do formato do checkpoint. Muda sempre que `Checkpoint` (ou o que ele guarda) muda,
+/// já que o bincode não tolera campos novos; checkpoints de outra versão são recusados.
+const FORMAT_VERSION: u16 = 1;
+
+/// Um jogo da população, com tudo que é preciso para recriá-lo igual.
+#[derive(Serialize, Deserialize)]
+struct GameRecord {
+    network: NeuralNetwork,
+    position: (f32, f32),
+    seed: u64,
+    opponents: Vec<NeuralNetwork>,
+}
+
+/// Foto completa de um treino: configuração, população, geração, histórico de
+/// pontuações e o estado do gerador aleatório. Retomar a partir dela continua o
+/// treino exatamente de onde parou.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub config: TrainingConfig,
+    pub generation: usize,
+    pub history: Vec<GenerationStats>,
+    rng: TrainingRng,
+    games: Vec<GameRecord>,
+    strategy: Option<EsState>,
+    league: League,
+}
+
+impl Checkpoint {
+    /// Copia o estado atual do treino.
+    pub fn capture(config: &TrainingConfig, state: &TrainingState) -> Self {
+        let games = state
+            .games
+            .iter()
+            .map(|game| GameRecord {
+                network: game.neural_network.lock().unwrap().clone(),
+                position: (game.pong.position.x, game.pong.position.y),
+                seed: game.seed,
+                opponents: game.opponents.clone(),
+            })
+            .collect();
+
+        Checkpoint {
+            config: config.clone(),
+            generation: state.generation,
+            history: state.history.clone(),
+            rng: state.rng.clone(),
+            games,
+            strategy: state.strategy.clone(),
+            league: state.league.clone(),
+        }
+    }
+
+    /// Salva o checkpoint em `path`. Escreve primeiro num arquivo temporário, garante que
+    /// ele chegou ao disco e só então o renomeia, para que uma queda ou um erro no meio da
+    /// escrita não corrompa o checkpoint anterior.
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let tmp_path = format!("{}.tmp", path);
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, self)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0; 6];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(format!("{} não é um checkpoint ou foi gravado por uma versão antiga do treino", path).into());
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != FORMAT_VERSION {
+            return Err(format!(
+                "{} foi gravado na versão {} do formato de checkpoint; esta versão do treino lê só a {}",
+                path, version, FORMAT_VERSION
+            )
+            .into());
+        }
+        let checkpoint = bincode::deserialize_from(reader)?;
+        Ok(checkpoint)
+    }
+
+    /// Recria a configuração e o estado do treino salvos.
+    pub fn into_state(self) -> (TrainingConfig, TrainingState) {
+        let games = self
+            .games
+            .into_iter()
+            .map(|record| {
+                let nn = Arc::new(Mutex::new(record.network));
+                let position = vec2(record.position.0, record.position.1);
+                GamePack::for_training(nn, position, record.seed, record.opponents, &self.config)
+            })
+            .collect();
+
+        let state = TrainingState {
+            games,
+            generation: self.generation,
+            history: self.history,
+            rng: self.rng,
+            strategy: self.strategy,
+            league: self.league,
+        };
+        (self.config, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::training::population::play_all_games;
+
+    fn temp_path(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("game_quad_checkpoint_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name).to_string_lossy().into_owned()
+    }
+
+    fn small_training() -> (TrainingConfig, TrainingState) {
+        let config = TrainingConfig {
+            games: 6,
+            max_steps: 600,
+            initial_network: None,
+            output: temp_path("best.bin"),
+            ..TrainingConfig::default()
+        };
+        let state = TrainingState::new(&config, 5).unwrap();
+        (config, state)
+    }
+
+    #[test]
+    fn resumed_training_matches_the_original() {
+        let (config, mut state) = small_training();
+        let path = temp_path("resume.ckpt");
+        Checkpoint::capture(&config, &state).save(&path).unwrap();
+        let (config2, mut resumed) = Checkpoint::load(&path).unwrap().into_state();
+
+        play_all_games(&mut state.games, config.max_steps, 1);
+        play_all_games(&mut resumed.games, config2.max_steps, 1);
+        let best = state.finish_generation(&config).best;
+        assert_eq!(resumed.finish_generation(&config2).best, best);
+    }
+
+    #[test]
+    fn rejects_checkpoints_without_header_or_from_other_versions() {
+        let (config, state) = small_training();
+        let checkpoint = Checkpoint::capture(&config, &state);
+
+        let old = temp_path("old.ckpt");
+        bincode::serialize_into(File::create(&old).unwrap(), &checkpoint).unwrap();
+        let error = Checkpoint::load(&old).err().unwrap().to_string();
+        assert!(error.contains("versão antiga"), "{}", error);
+
+        let newer = temp_path("newer.ckpt");
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((FORMAT_VERSION + 1).to_le_bytes());
+        bytes.extend(bincode::serialize(&checkpoint).unwrap());
+        fs::write(&newer, bytes).unwrap();
+        assert!(Checkpoint::load(&newer).is_err());
+    }
+}
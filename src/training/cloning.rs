@@ -0,0 +1,240 @@
+//! Clonagem de comportamento: ajusta uma rede, por retropropagação, às decisões gravadas
+//! de um jogador (ver `game::recording`). A rede resultante pode semear o treino genético
+//! via `--initial-network`.
+
+use std::error::Error;
+
+use macroquad::math::vec2;
+use rand::SeedableRng;
+
+use crate::cli::Args;
+use crate::game::observation::ObservationConfig;
+use crate::game::recording::RecordedFrame;
+use crate::neural_network::neural_network_f::{
+    network_inputs, output_targets, NeuralNetwork, NeuralNetworkModel, CONTROLLER_OUTPUTS,
+};
+use crate::neural_network::neuron::ActivationFunction;
+use crate::neural_network::optimizer::{Loss, OptimizerKind, Sample};
+use crate::training::TrainingRng;
+
+/// Parâmetros do treino supervisionado a partir de uma gravação.
+#[derive(Debug, Clone)]
+pub struct CloningConfig {
+    pub epochs: usize,
+    pub batch_size: usize,
+    pub learning_rate: f64,
+    /// Momento do SGD; ignorado pelo Adam.
+    pub momentum: f64,
+    pub optimizer: OptimizerKind,
+    pub loss: Loss,
+    /// Topologia da rede treinada do zero.
+    pub topology: NeuralNetworkModel,
+    /// Rede de partida. `None` começa de uma rede aleatória com `topology`.
+    pub initial_network: Option<String>,
+    /// Semente do embaralhamento e dos pesos iniciais.
+    pub seed: u64,
+}
+
+impl Default for CloningConfig {
+    fn default() -> Self {
+        CloningConfig {
+            epochs: 50,
+            batch_size: 32,
+            learning_rate: 0.01,
+            momentum: 0.9,
+            optimizer: OptimizerKind::Adam,
+            loss: Loss::BinaryCrossEntropy,
+            topology: NeuralNetworkModel::new(
+                ObservationConfig::default().size(),
+                vec![5, CONTROLLER_OUTPUTS],
+                vec![ActivationFunction::Relu, ActivationFunction::Sigmoid],
+            ),
+            initial_network: None,
+            seed: 0,
+        }
+    }
+}
+
+impl CloningConfig {
+    /// Monta a configuração a partir das flags da linha de comando.
+    pub fn from_args(args: &Args) -> Result<Self, String> {
+        let mut config = CloningConfig::default();
+        if let Some(epochs) = args.parse_value("epochs")? {
+            config.epochs = epochs;
+        }
+        if let Some(batch_size) = args.parse_value("batch-size")? {
+            config.batch_size = batch_size;
+        }
+        if let Some(learning_rate) = args.parse_value("learning-rate")? {
+            config.learning_rate = learning_rate;
+        }
+        if let Some(momentum) = args.parse_value("momentum")? {
+            config.momentum = momentum;
+        }
+        if let Some(optimizer) = args.parse_value("optimizer")? {
+            config.optimizer = optimizer;
+        }
+        if let Some(loss) = args.parse_value("loss")? {
+            config.loss = loss;
+        }
+        if let Some(layers) = args.parse_list("layers")? {
+            config.topology.hidden_layers_sizes = layers;
+        }
+        if let Some(activations) = args.parse_list::<ActivationFunction>("activations")? {
+            config.topology.activation_functions = activations;
+        }
+        config.initial_network = args.parse_value("initial-network")?;
+        if let Some(seed) = args.parse_value("seed")? {
+            config.seed = seed;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.epochs == 0 || self.batch_size == 0 {
+            return Err("epochs e batch_size devem ser maiores que zero".into());
+        }
+        if self.learning_rate <= 0.0 {
+            return Err("learning_rate deve ser positiva".into());
+        }
+        if self.topology.hidden_layers_sizes.len() != self.topology.activation_functions.len() {
+            return Err("topology: layers e activations devem ter o mesmo tamanho".into());
+        }
+        if self.topology.hidden_layers_sizes.last() != Some(&CONTROLLER_OUTPUTS) {
+            return Err(format!("a última camada deve ter {} neurônios", CONTROLLER_OUTPUTS));
+        }
+        // A gravação só guarda bola e jogador, o suficiente para as features padrão
+        let inputs = ObservationConfig::default().size();
+        if self.topology.input_layer_size != inputs {
+            return Err(format!(
+                "a clonagem só treina redes com as features de observação padrão ({} inputs)",
+                inputs
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Converte os passos gravados em exemplos de treino, com os mesmos inputs que a rede
+/// recebe como controlador e a decisão do jogador como saída esperada.
+pub fn samples_from_frames(frames: &[RecordedFrame]) -> Vec<Sample> {
+    frames
+        .iter()
+        .map(|frame| {
+            let inputs = network_inputs(
+                vec2(frame.ball_position.0, frame.ball_position.1),
+                vec2(frame.ball_velocity.0, frame.ball_velocity.1),
+                vec2(frame.player_position.0, frame.player_position.1),
+            );
+            Sample {
+                inputs: inputs.to_vec(),
+                targets: output_targets(frame.direction, frame.speed).to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// Treina uma rede para imitar `frames`. `on_epoch` recebe o número da época e a perda média.
+pub fn clone_behavior(
+    frames: &[RecordedFrame],
+    config: &CloningConfig,
+    mut on_epoch: impl FnMut(usize, f64),
+) -> Result<NeuralNetwork, Box<dyn Error>> {
+    if frames.is_empty() {
+        return Err("a gravação não tem nenhum passo".into());
+    }
+
+    let mut rng = TrainingRng::seed_from_u64(config.seed);
+    let mut nn = match &config.initial_network {
+        Some(path) => NeuralNetwork::load_neural_network(path)?,
+        None => config.topology.generate_with_rng(&mut rng),
+    };
+    // A gravação só guarda bola e jogador, os inputs de `network_inputs`
+    if nn.observation != ObservationConfig::default() {
+        return Err("a clonagem só treina redes com as features de observação padrão".into());
+    }
+    let output_size = nn.layers.last().map_or(0, |layer| layer.neurons.len());
+    if output_size != CONTROLLER_OUTPUTS {
+        return Err(format!("a rede precisa ter {} saídas, tem {}", CONTROLLER_OUTPUTS, output_size).into());
+    }
+
+    let samples = samples_from_frames(frames);
+    let mut optimizer = config.optimizer.build(config.learning_rate, config.momentum);
+    for epoch in 1..=config.epochs {
+        let loss = nn.train_epoch(&samples, config.batch_size, config.loss, optimizer.as_mut(), &mut rng);
+        on_epoch(epoch, loss);
+    }
+    Ok(nn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::controller::PlayerDirection;
+    use crate::game::observation::ObservationFeature;
+    use crate::game::pong::FIXED_DT;
+
+    fn frame(direction: PlayerDirection, speed: f64) -> RecordedFrame {
+        RecordedFrame {
+            ball_position: (300.0, 120.0),
+            ball_velocity: (-480.0, 60.0),
+            player_position: (100.0, 200.0),
+            direction,
+            speed,
+        }
+    }
+
+    #[test]
+    fn samples_hold_the_controller_inputs_and_the_recorded_decision() {
+        let frames = [
+            frame(PlayerDirection::Up, 0.8),
+            frame(PlayerDirection::Down, 0.3),
+            frame(PlayerDirection::None, 0.0),
+        ];
+        let samples = samples_from_frames(&frames);
+
+        let velocity = (-480.0 * FIXED_DT) as f64;
+        let vertical = (60.0 * FIXED_DT) as f64;
+        for sample in &samples {
+            assert_eq!(sample.inputs, vec![200.0, 80.0, velocity, vertical]);
+        }
+        assert_eq!(samples[0].targets, vec![1.0, 0.0, 0.8]);
+        assert_eq!(samples[1].targets, vec![0.0, 1.0, 0.3]);
+        assert_eq!(samples[2].targets, vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn rejects_networks_with_other_observations() {
+        let mut config = CloningConfig::default();
+        config.topology.input_layer_size = 6;
+        assert!(config.validate().is_err());
+
+        // Mesmo tamanho de entrada, mas features que a gravação não tem como reproduzir
+        let observation = ObservationConfig::new(
+            vec![ObservationFeature::SignedOffset, ObservationFeature::BallVelocity],
+            false,
+        );
+        let path = std::env::temp_dir().join(format!("game_quad_cloning_{}.bin", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let mut config = CloningConfig {
+            epochs: 1,
+            ..CloningConfig::default()
+        };
+        let mut rng = TrainingRng::seed_from_u64(1);
+        config
+            .topology
+            .generate_with_rng(&mut rng)
+            .with_observation(observation)
+            .save_neural_network(&path)
+            .unwrap();
+        config.initial_network = Some(path.clone());
+
+        let frames = [frame(PlayerDirection::Up, 1.0)];
+        let result = clone_behavior(&frames, &config, |_, _| {});
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+        config.initial_network = None;
+        assert!(clone_behavior(&frames, &config, |_, _| {}).is_ok());
+    }
+}
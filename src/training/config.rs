@@ -0,0 +1,469 @@
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Args;
+use crate::game::controller::Latency;
+use crate::game::observation::{ObservationConfig, ObservationFeature};
+use crate::game::pong::MatchRule;
+use crate::neural_network::neural_network_f::{NeuralNetworkModel, CONTROLLER_OUTPUTS};
+use crate::neural_network::neuron::ActivationFunction;
+use crate::training::crossover::CrossoverOperator;
+use crate::training::es::EsConfig;
+use crate::training::fitness::{FitnessAggregation, FitnessConfig};
+use crate::training::league::{LeagueConfig, OpponentKind};
+use crate::training::mutation::MutationKind;
+use crate::training::neat::NeatConfig;
+use crate::training::selection::SelectionKind;
+
+/// Como cada nova geração é produzida a partir da anterior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvolutionAlgorithm {
+    /// Algoritmo genético com topologia fixa: só os pesos evoluem.
+    Genetic,
+    /// Neuroevolução com mudanças de topologia e especiação (ver `training::neat`).
+    Neat,
+    /// OpenAI-ES sobre o vetor de pesos (ver `training::es`).
+    Es,
+    /// CMA-ES sobre o vetor de pesos (ver `training::es`).
+    CmaEs,
+}
+
+impl FromStr for EvolutionAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "genetic" => Ok(EvolutionAlgorithm::Genetic),
+            "neat" => Ok(EvolutionAlgorithm::Neat),
+            "es" => Ok(EvolutionAlgorithm::Es),
+            "cma-es" | "cmaes" => Ok(EvolutionAlgorithm::CmaEs),
+            _ => Err(format!("algoritmo desconhecido: {} (use genetic, neat, es ou cma-es)", s)),
+        }
+    }
+}
+
+/// Hiperparâmetros do treino genético.
+///
+/// Podem vir de um arquivo JSON (`--config treino.json`) e cada campo pode ser sobrescrito
+/// por flags de linha de comando, o que permite varreduras sem recompilar.
+/// Campos ausentes no arquivo assumem o valor de `TrainingConfig::default()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrainingConfig {
+    /// Algoritmo de evolução.
+    pub algorithm: EvolutionAlgorithm,
+    /// Quantidade de jogos (e redes) por geração.
+    pub games: usize,
+    /// Quantos jogos são desenhados por quadro no modo com janela.
+    pub games_drawn: usize,
+    /// Quantos jogos por linha na grade desenhada.
+    pub games_line: usize,
+    /// Fração da população copiada sem mutação para a próxima geração (elitismo). 0 desativa.
+    pub elite_fraction: f64,
+    /// Estratégia de seleção dos pais.
+    pub selection: SelectionKind,
+    /// Tamanho do torneio na seleção `Tournament`.
+    pub tournament_size: usize,
+    /// Fração da população de onde saem os pais na seleção `Truncation`.
+    pub selection_fraction: f64,
+    /// Operador de mutação.
+    pub mutation: MutationKind,
+    /// Probabilidade de cada peso sofrer mutação.
+    pub mutation_rate: f64,
+    /// Amplitude da mutação: o intervalo ±`mutation_magnitude` da `Uniform` ou o desvio das normais.
+    pub mutation_magnitude: f64,
+    /// Menor desvio a que a mutação `Adaptive` pode chegar.
+    pub mutation_min_magnitude: f64,
+    /// Fator, em (0, 1], com que a mutação `Adaptive` encolhe o desvio quando o treino progride.
+    pub mutation_annealing: f64,
+    /// Operador usado para cruzar dois pais.
+    pub crossover: CrossoverOperator,
+    /// Probabilidade de cada filho ser gerado por cruzamento (em vez de cópia de um só pai)
+    /// antes da mutação. 0 desativa o cruzamento.
+    pub crossover_rate: f64,
+    /// Parâmetros das mutações de topologia e da especiação, usados com `algorithm: Neat`.
+    pub neat: NeatConfig,
+    /// Parâmetros das estratégias evolutivas, usados com `algorithm: Es` ou `CmaEs`.
+    /// Nelas a população inteira é amostrada da distribuição, sem elite.
+    pub es: EsConfig,
+    /// Pesos dos objetivos que compõem a aptidão de cada episódio.
+    pub fitness: FitnessConfig,
+    /// Episódios jogados por rede a cada geração, cada um com uma semente diferente.
+    pub episodes: usize,
+    /// Como a aptidão dos episódios vira a aptidão da rede.
+    pub fitness_aggregation: FitnessAggregation,
+    /// Contra quem a população joga: o bot fixo ou a liga de campeões (self-play).
+    pub opponent: OpponentKind,
+    /// Parâmetros da liga, usados com `opponent: League`.
+    pub league: LeagueConfig,
+    /// Topologia e funções de ativação das redes geradas do zero.
+    pub topology: NeuralNetworkModel,
+    /// Features que as redes geradas do zero recebem; o tamanho delas é a entrada da topologia.
+    pub observation: ObservationConfig,
+    /// Rede usada para semear a população inicial. `None` gera redes aleatórias a partir de `topology`.
+    pub initial_network: Option<String>,
+    /// Onde salvar a melhor rede de cada geração. Com extensão `.json` a rede é gravada em JSON.
+    pub output: String,
+    /// Máximo de passos de simulação por partida; ao atingi-lo a partida é encerrada.
+    pub max_steps: usize,
+    /// Quando cada partida termina: no primeiro a N pontos ou ao fim de um tempo.
+    pub match_rule: MatchRule,
+    /// Atraso de percepção e intervalo entre decisões das redes (a treinada e os campeões).
+    pub latency: Latency,
+    /// Encerra o treino após esta quantidade de gerações.
+    pub max_generations: Option<usize>,
+    /// Encerra o treino quando a melhor pontuação de uma geração atinge este valor.
+    pub target_fitness: Option<f64>,
+    /// Semente do treino. `None` sorteia uma (e a imprime, para poder repetir o treino).
+    pub seed: Option<u64>,
+    /// Threads usadas para avaliar a população no modo sem janela; 0 usa todos os núcleos.
+    pub threads: usize,
+    /// Arquivo de checkpoint do treino. `None` desativa os checkpoints.
+    pub checkpoint: Option<String>,
+    /// A cada quantas gerações o checkpoint é salvo.
+    pub checkpoint_every: usize,
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        TrainingConfig {
+            algorithm: EvolutionAlgorithm::Genetic,
+            games: 1900,
+            games_drawn: 100,
+            games_line: 20,
+            elite_fraction: 0.12,
+            selection: SelectionKind::Truncation,
+            tournament_size: 3,
+            selection_fraction: 0.12,
+            mutation: MutationKind::Uniform,
+            mutation_rate: 0.35,
+            mutation_magnitude: 0.4,
+            mutation_min_magnitude: 0.02,
+            mutation_annealing: 0.9,
+            crossover: CrossoverOperator::Uniform,
+            crossover_rate: 0.0,
+            neat: NeatConfig::default(),
+            es: EsConfig::default(),
+            fitness: FitnessConfig::default(),
+            episodes: 1,
+            fitness_aggregation: FitnessAggregation::Mean,
+            opponent: OpponentKind::Bot,
+            league: LeagueConfig::default(),
+            topology: NeuralNetworkModel::new(
+                4,
+                vec![5, 3],
+                vec![ActivationFunction::Relu, ActivationFunction::Sigmoid],
+            ),
+            observation: ObservationConfig::default(),
+            initial_network: Some("best_nn.bin".to_owned()),
+            output: "best_nn.bin".to_owned(),
+            max_steps: 2 * 60 * 60,
+            match_rule: MatchRule::default(),
+            latency: Latency::default(),
+            max_generations: None,
+            target_fitness: None,
+            seed: None,
+            threads: 0,
+            checkpoint: None,
+            checkpoint_every: 10,
+        }
+    }
+}
+
+impl TrainingConfig {
+    /// Lê a configuração de um arquivo JSON.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        let config: TrainingConfig = serde_json::from_str(&text)?;
+        Ok(config)
+    }
+
+    /// Monta a configuração a partir da linha de comando: parte do arquivo de `--config`
+    /// (ou do padrão) e aplica as flags por cima.
+    pub fn from_args(args: &Args) -> Result<Self, Box<dyn Error>> {
+        let mut config = match args.value("config") {
+            Some(path) => TrainingConfig::load(path)?,
+            None => TrainingConfig::default(),
+        };
+        config.apply_args(args)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Sobrescreve os campos que aparecem como flags em `args`.
+    pub fn apply_args(&mut self, args: &Args) -> Result<(), String> {
+        if let Some(algorithm) = args.parse_value("algorithm")? {
+            self.algorithm = algorithm;
+        }
+        if let Some(compatibility_threshold) = args.parse_value("compatibility-threshold")? {
+            self.neat.compatibility_threshold = compatibility_threshold;
+        }
+        if let Some(sigma) = args.parse_value("es-sigma")? {
+            self.es.sigma = sigma;
+        }
+        if let Some(learning_rate) = args.parse_value("es-learning-rate")? {
+            self.es.learning_rate = learning_rate;
+        }
+        if let Some(hits) = args.parse_value("fitness-hits")? {
+            self.fitness.hits = hits;
+        }
+        if let Some(rally_length) = args.parse_value("fitness-rally")? {
+            self.fitness.rally_length = rally_length;
+        }
+        if let Some(points_won) = args.parse_value("fitness-points-won")? {
+            self.fitness.points_won = points_won;
+        }
+        if let Some(points_conceded) = args.parse_value("fitness-points-conceded")? {
+            self.fitness.points_conceded = points_conceded;
+        }
+        if let Some(travel_distance) = args.parse_value("fitness-travel")? {
+            self.fitness.travel_distance = travel_distance;
+        }
+        if let Some(time_survived) = args.parse_value("fitness-time")? {
+            self.fitness.time_survived = time_survived;
+        }
+        if let Some(opponent) = args.parse_value("opponent")? {
+            self.opponent = opponent;
+        }
+        if let Some(pool_size) = args.parse_value("league-size")? {
+            self.league.pool_size = pool_size;
+        }
+        if let Some(episodes) = args.parse_value("episodes")? {
+            self.episodes = episodes;
+        }
+        if let Some(aggregation) = args.parse_value("aggregation")? {
+            self.fitness_aggregation = aggregation;
+        }
+        if let Some(promote_every) = args.parse_value("promote-every")? {
+            self.league.promote_every = promote_every;
+        }
+        if let Some(games) = args.parse_value("games")? {
+            self.games = games;
+        }
+        if let Some(games_drawn) = args.parse_value("games-drawn")? {
+            self.games_drawn = games_drawn;
+        }
+        if let Some(games_line) = args.parse_value("games-line")? {
+            self.games_line = games_line;
+        }
+        if let Some(elite_fraction) = args.parse_value("elite-fraction")? {
+            self.elite_fraction = elite_fraction;
+        }
+        if let Some(selection) = args.parse_value("selection")? {
+            self.selection = selection;
+        }
+        if let Some(tournament_size) = args.parse_value("tournament-size")? {
+            self.tournament_size = tournament_size;
+        }
+        if let Some(selection_fraction) = args.parse_value("selection-fraction")? {
+            self.selection_fraction = selection_fraction;
+        }
+        if let Some(mutation) = args.parse_value("mutation")? {
+            self.mutation = mutation;
+        }
+        if let Some(mutation_rate) = args.parse_value("mutation-rate")? {
+            self.mutation_rate = mutation_rate;
+        }
+        if let Some(mutation_magnitude) = args.parse_value("mutation-magnitude")? {
+            self.mutation_magnitude = mutation_magnitude;
+        }
+        if let Some(mutation_min_magnitude) = args.parse_value("mutation-min-magnitude")? {
+            self.mutation_min_magnitude = mutation_min_magnitude;
+        }
+        if let Some(mutation_annealing) = args.parse_value("mutation-annealing")? {
+            self.mutation_annealing = mutation_annealing;
+        }
+        if let Some(crossover) = args.parse_value("crossover")? {
+            self.crossover = crossover;
+        }
+        if let Some(crossover_rate) = args.parse_value("crossover-rate")? {
+            self.crossover_rate = crossover_rate;
+        }
+        if let Some(input_size) = args.parse_value("input-size")? {
+            self.topology.input_layer_size = input_size;
+        }
+        if let Some(layers) = args.parse_list("layers")? {
+            self.topology.hidden_layers_sizes = layers;
+        }
+        if let Some(activations) = args.parse_list::<ActivationFunction>("activations")? {
+            self.topology.activation_functions = activations;
+        }
+        // `--features` já define o tamanho da entrada, sem precisar de `--input-size`
+        if let Some(features) = args.parse_list::<ObservationFeature>("features")? {
+            self.observation.features = features;
+            self.topology.input_layer_size = self.observation.size();
+        }
+        if args.flag("normalize") {
+            self.observation.normalize = true;
+        }
+        if let Some(initial_network) = args.parse_value::<String>("initial-network")? {
+            self.initial_network = match initial_network.as_str() {
+                "none" => None,
+                _ => Some(initial_network),
+            };
+        }
+        if let Some(output) = args.parse_value("output")? {
+            self.output = output;
+        }
+        if let Some(max_steps) = args.parse_value("max-steps")? {
+            self.max_steps = max_steps;
+        }
+        if let Some(match_rule) = args.parse_value("match-rule")? {
+            self.match_rule = match_rule;
+        }
+        // Em milissegundos na linha de comando, em segundos na configuração
+        if let Some(delay_ms) = args.parse_value::<f32>("reaction-ms")? {
+            self.latency.delay = delay_ms / 1000.0;
+        }
+        if let Some(interval_ms) = args.parse_value::<f32>("decision-ms")? {
+            self.latency.decision_interval = interval_ms / 1000.0;
+        }
+        if let Some(max_generations) = args.parse_value("generations")? {
+            self.max_generations = Some(max_generations);
+        }
+        if let Some(target_fitness) = args.parse_value("target-fitness")? {
+            self.target_fitness = Some(target_fitness);
+        }
+        if let Some(seed) = args.parse_value("seed")? {
+            self.seed = Some(seed);
+        }
+        if let Some(threads) = args.parse_value("threads")? {
+            self.threads = threads;
+        }
+        if let Some(checkpoint) = args.parse_value::<String>("checkpoint")? {
+            self.checkpoint = match checkpoint.as_str() {
+                "none" => None,
+                _ => Some(checkpoint),
+            };
+        }
+        if let Some(checkpoint_every) = args.parse_value("checkpoint-every")? {
+            self.checkpoint_every = checkpoint_every;
+        }
+        // A rede inicial joga com as features gravadas nela; as flags seriam ignoradas
+        if (args.flag("features") || args.flag("normalize")) && self.initial_network.is_some() {
+            return Err(
+                "--features e --normalize só valem para redes geradas do zero; use --initial-network none".into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Verifica combinações de valores que o treino não consegue usar.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.games == 0 {
+            return Err("games deve ser maior que zero".into());
+        }
+        if self.episodes == 0 {
+            return Err("episodes deve ser maior que zero".into());
+        }
+        if self.max_steps == 0 {
+            return Err("max_steps deve ser maior que zero".into());
+        }
+        if self.checkpoint_every == 0 {
+            return Err("checkpoint_every deve ser maior que zero".into());
+        }
+        if self.games_line == 0 {
+            return Err("games_line deve ser maior que zero".into());
+        }
+        if !(0.0..=1.0).contains(&self.elite_fraction) {
+            return Err("elite_fraction deve estar em [0, 1]".into());
+        }
+        if self.tournament_size == 0 {
+            return Err("tournament_size deve ser maior que zero".into());
+        }
+        if !(self.selection_fraction > 0.0 && self.selection_fraction <= 1.0) {
+            return Err("selection_fraction deve estar em (0, 1]".into());
+        }
+        if !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err("mutation_rate deve estar em [0, 1]".into());
+        }
+        if !(0.0..=1.0).contains(&self.crossover_rate) {
+            return Err("crossover_rate deve estar em [0, 1]".into());
+        }
+        let magnitude = |value: f64| value.is_finite() && value >= 0.0;
+        if !magnitude(self.mutation_magnitude) || !magnitude(self.mutation_min_magnitude) {
+            return Err("mutation_magnitude e mutation_min_magnitude devem ser números finitos e não negativos".into());
+        }
+        if !(self.mutation_annealing > 0.0 && self.mutation_annealing <= 1.0) {
+            return Err("mutation_annealing deve estar em (0, 1]".into());
+        }
+        if self.topology.hidden_layers_sizes.len() != self.topology.activation_functions.len() {
+            return Err("topology: layers e activations devem ter o mesmo tamanho".into());
+        }
+        if self.topology.hidden_layers_sizes.contains(&0) {
+            return Err("topology: as camadas não podem ser vazias".into());
+        }
+        if self.topology.hidden_layers_sizes.last() != Some(&CONTROLLER_OUTPUTS) {
+            return Err(format!(
+                "topology: a última camada deve ter {} neurônios",
+                CONTROLLER_OUTPUTS
+            ));
+        }
+        if self.observation.size() != self.topology.input_layer_size {
+            return Err(format!(
+                "observation: as features somam {} inputs, mas topology.input_layer_size é {}",
+                self.observation.size(),
+                self.topology.input_layer_size
+            ));
+        }
+        self.neat.validate()?;
+        let hidden_layers = self.topology.hidden_layers_sizes.len() - 1;
+        if self.algorithm == EvolutionAlgorithm::Neat
+            && self.initial_network.is_none()
+            && hidden_layers > self.neat.max_hidden_layers
+        {
+            return Err(format!(
+                "neat: a topologia inicial tem {} camadas ocultas, acima de max_hidden_layers ({})",
+                hidden_layers, self.neat.max_hidden_layers
+            ));
+        }
+        self.es.validate()?;
+        self.league.validate()?;
+        self.fitness.validate()?;
+        self.latency.validate()?;
+        Ok(())
+    }
+
+    /// Indica se o treino deve parar depois da geração descrita por `generation` e `best_fitness`.
+    pub fn should_stop(&self, generation: usize, best_fitness: f64) -> bool {
+        self.max_generations.is_some_and(|max| generation >= max)
+            || self.target_fitness.is_some_and(|target| best_fitness >= target)
+    }
+
+    /// Quantidade de redes mantidas como elite.
+    pub fn elite_count(&self) -> usize {
+        let elite_count = (self.elite_fraction * self.games as f64).ceil() as usize;
+        elite_count.min(self.games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Args {
+        Args::parse(line.split_whitespace().map(str::to_owned))
+    }
+
+    #[test]
+    fn features_set_the_input_size_of_new_networks() {
+        let mut config = TrainingConfig::default();
+        config
+            .apply_args(&args("--initial-network none --features signed-offset,predicted-intercept --normalize"))
+            .unwrap();
+        assert_eq!(config.topology.input_layer_size, 3);
+        assert!(config.observation.normalize);
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn features_conflict_with_an_initial_network() {
+        let mut config = TrainingConfig::default();
+        assert!(config.initial_network.is_some());
+        assert!(config.apply_args(&args("--features signed-offset")).is_err());
+        assert!(config.apply_args(&args("--normalize")).is_err());
+    }
+}
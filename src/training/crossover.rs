@@ -0,0 +1,130 @@
+//! Operadores de cruzamento entre duas redes com a mesma topologia.
+
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::network_file::Topology;
+use crate::neural_network::neural_network_f::NeuralNetwork;
+
+/// Como o filho herda os parâmetros dos dois pais.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CrossoverOperator {
+    /// Cada peso (e bias) vem de um dos pais, sorteado independentemente.
+    Uniform,
+    /// Cada neurônio vem inteiro de um dos pais.
+    Neuron,
+    /// Cada camada vem inteira de um dos pais.
+    Layer,
+}
+
+impl FromStr for CrossoverOperator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(CrossoverOperator::Uniform),
+            "neuron" => Ok(CrossoverOperator::Neuron),
+            "layer" => Ok(CrossoverOperator::Layer),
+            _ => Err(format!("operador de cruzamento desconhecido: {} (use uniform, neuron ou layer)", s)),
+        }
+    }
+}
+
+impl CrossoverOperator {
+    /// Gera um filho de `a` e `b`, cada parte vindo de um dos pais com 50% de chance.
+    /// Se as topologias forem diferentes não há como alinhar os pesos, e o filho é uma cópia de `a`.
+    pub fn crossover<R: Rng + ?Sized>(self, a: &NeuralNetwork, b: &NeuralNetwork, rng: &mut R) -> NeuralNetwork {
+        let mut child = a.clone();
+        if Topology::of(a) != Topology::of(b) {
+            return child;
+        }
+
+        for (child_layer, other_layer) in child.layers.iter_mut().zip(&b.layers) {
+            let neurons = child_layer.neurons.iter_mut().zip(&other_layer.neurons);
+            match self {
+                CrossoverOperator::Layer => {
+                    if rng.random_bool(0.5) {
+                        child_layer.neurons.clone_from(&other_layer.neurons);
+                    }
+                }
+                CrossoverOperator::Neuron => {
+                    for (child_neuron, other_neuron) in neurons {
+                        if rng.random_bool(0.5) {
+                            child_neuron.clone_from(other_neuron);
+                        }
+                    }
+                }
+                CrossoverOperator::Uniform => {
+                    for (child_neuron, other_neuron) in neurons {
+                        for (weight, &other) in child_neuron.weights.iter_mut().zip(&other_neuron.weights) {
+                            if rng.random_bool(0.5) {
+                                *weight = other;
+                            }
+                        }
+                        if rng.random_bool(0.5) {
+                            child_neuron.bias = other_neuron.bias;
+                        }
+                    }
+                }
+            }
+        }
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::neural_network::neuron::ActivationFunction;
+
+    const OPERATORS: [CrossoverOperator; 3] =
+        [CrossoverOperator::Uniform, CrossoverOperator::Neuron, CrossoverOperator::Layer];
+
+    fn network(layers: &[usize], rng: &mut ChaCha8Rng) -> NeuralNetwork {
+        let activations = vec![ActivationFunction::Tanh; layers.len()];
+        NeuralNetwork::new_with_rng(4, layers, &activations, rng).unwrap()
+    }
+
+    fn parameters(nn: &NeuralNetwork) -> Vec<f64> {
+        nn.layers
+            .iter()
+            .flat_map(|layer| &layer.neurons)
+            .flat_map(|neuron| neuron.weights.iter().copied().chain([neuron.bias]))
+            .collect()
+    }
+
+    #[test]
+    fn children_keep_the_parents_topology_and_genes() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let a = network(&[6, 5, 3], &mut rng);
+        let b = network(&[6, 5, 3], &mut rng);
+        let (genes_a, genes_b) = (parameters(&a), parameters(&b));
+
+        for operator in OPERATORS {
+            for _ in 0..20 {
+                let child = operator.crossover(&a, &b, &mut rng);
+                assert_eq!(Topology::of(&child), Topology::of(&a), "{:?}", operator);
+                let genes = parameters(&child);
+                for (i, gene) in genes.iter().enumerate() {
+                    assert!(*gene == genes_a[i] || *gene == genes_b[i], "{:?}", operator);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_topologies_copy_the_first_parent() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let a = network(&[6, 3], &mut rng);
+        let b = network(&[5, 3], &mut rng);
+        for operator in OPERATORS {
+            let child = operator.crossover(&a, &b, &mut rng);
+            assert_eq!(parameters(&child), parameters(&a));
+        }
+    }
+}
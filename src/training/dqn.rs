@@ -0,0 +1,340 @@
+//! Aprendizado por reforço com DQN sobre o `PongEnv`: a rede estima o valor Q de cada ação
+//! (subir, descer, parar) e aprende por diferença temporal com lotes sorteados de um buffer
+//! de replay, mirando numa cópia de si mesma (a rede alvo) que só é atualizada de tempos
+//! em tempos.
+//!
+//! A rede Q não serve direto de controlador, que lê as saídas como teclas. Ao fim do treino
+//! ela ganha duas camadas fixas (`greedy_head`) que transformam "a ação de maior Q" nas
+//! saídas `[sobe, desce, velocidade]`, e o resultado é uma rede comum.
+
+use std::collections::VecDeque;
+use std::error::Error;
+
+use rand::{Rng, SeedableRng};
+
+use crate::game::controller::PlayerDirection;
+use crate::game::env::{Environment, PongEnv};
+use crate::game::observation::Observation;
+use crate::neural_network::layers::Layer;
+use crate::neural_network::neural_network_f::{NeuralNetwork, NeuralNetworkModel, CONTROLLER_OUTPUTS};
+use crate::neural_network::neuron::{ActivationFunction, Neuron, NeuronTrait};
+use crate::neural_network::optimizer::{Loss, NetworkGradients, Optimizer};
+use crate::training::rl::{RlConfig, RlStats};
+use crate::training::TrainingRng;
+
+/// Ações discretas, na ordem das saídas da rede Q.
+const ACTIONS: [PlayerDirection; 3] = [PlayerDirection::Up, PlayerDirection::Down, PlayerDirection::None];
+
+/// Bias da saída de velocidade de `greedy_head`: a sigmoid dele é praticamente 1.
+const FULL_SPEED_BIAS: f64 = 10.0;
+
+/// Parâmetros específicos do DQN.
+#[derive(Debug, Clone)]
+pub struct DqnConfig {
+    /// Transições guardadas no buffer de replay; ao encher, as mais antigas saem.
+    pub replay_capacity: usize,
+    /// Transições sorteadas do buffer a cada passo de treino.
+    pub replay_batch: usize,
+    /// Passos jogados antes do primeiro treino, para o buffer não começar quase vazio.
+    pub learning_starts: usize,
+    /// A cada quantos passos a rede alvo recebe os pesos da rede Q.
+    pub target_update: usize,
+    /// Exploração ε-gulosa: ε cai linearmente de `epsilon_start` a `epsilon_end` ao longo
+    /// de `epsilon_decay_episodes` episódios.
+    pub epsilon_start: f64,
+    pub epsilon_end: f64,
+    pub epsilon_decay_episodes: usize,
+}
+
+impl Default for DqnConfig {
+    fn default() -> Self {
+        DqnConfig {
+            replay_capacity: 20_000,
+            replay_batch: 32,
+            learning_starts: 1000,
+            target_update: 1000,
+            epsilon_start: 1.0,
+            epsilon_end: 0.05,
+            epsilon_decay_episodes: 500,
+        }
+    }
+}
+
+impl DqnConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.replay_capacity == 0 || self.replay_batch == 0 || self.target_update == 0 {
+            return Err("dqn: replay_capacity, replay_batch e target_update devem ser maiores que zero".into());
+        }
+        if self.replay_batch > self.replay_capacity {
+            return Err("dqn: replay_batch não pode ser maior que replay_capacity".into());
+        }
+        if !(0.0..=1.0).contains(&self.epsilon_start) || !(0.0..=1.0).contains(&self.epsilon_end) {
+            return Err("dqn: epsilon_start e epsilon_end devem estar em [0, 1]".into());
+        }
+        Ok(())
+    }
+
+    /// ε do episódio `episode`.
+    fn epsilon(&self, episode: usize) -> f64 {
+        let progress = match self.epsilon_decay_episodes {
+            0 => 1.0,
+            decay => (episode as f64 / decay as f64).min(1.0),
+        };
+        self.epsilon_start + (self.epsilon_end - self.epsilon_start) * progress
+    }
+}
+
+/// Topologia padrão da rede Q: a última camada é linear, porque os valores Q não ficam em (0, 1).
+pub fn default_topology(input_size: usize) -> NeuralNetworkModel {
+    NeuralNetworkModel::new(
+        input_size,
+        vec![16, ACTIONS.len()],
+        vec![ActivationFunction::Relu, ActivationFunction::Linear],
+    )
+}
+
+/// Um passo jogado, guardado no buffer de replay.
+struct Transition {
+    observation: Observation,
+    action: usize,
+    reward: f64,
+    next: Observation,
+    /// A partida acabou neste passo: não há valor futuro a somar.
+    terminal: bool,
+}
+
+/// Últimas `capacity` transições jogadas.
+struct ReplayBuffer {
+    transitions: VecDeque<Transition>,
+    capacity: usize,
+}
+
+impl ReplayBuffer {
+    fn new(capacity: usize) -> Self {
+        ReplayBuffer {
+            transitions: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, transition: Transition) {
+        if self.transitions.len() == self.capacity {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(transition);
+    }
+
+    /// Sorteia `count` transições, com reposição.
+    fn sample<'a>(&'a self, count: usize, rng: &mut TrainingRng) -> Vec<&'a Transition> {
+        (0..count)
+            .map(|_| &self.transitions[rng.random_range(0..self.transitions.len())])
+            .collect()
+    }
+}
+
+/// Índice da ação de maior valor Q para `observation`.
+fn greedy_action(q: &mut NeuralNetwork, observation: &[f64]) -> usize {
+    q.feed(observation);
+    let values = q.get_output().unwrap();
+    (0..values.len()).fold(0, |best, i| if values[i] > values[best] { i } else { best })
+}
+
+/// Um passo de descida do erro de diferença temporal num lote sorteado de `buffer`.
+fn learn(
+    q: &mut NeuralNetwork,
+    target: &mut NeuralNetwork,
+    buffer: &ReplayBuffer,
+    config: &RlConfig,
+    optimizer: &mut dyn Optimizer,
+    rng: &mut TrainingRng,
+) {
+    let batch = buffer.sample(config.dqn.replay_batch, rng);
+    let mut gradients = NetworkGradients::zeros_like(q);
+    for transition in &batch {
+        let mut value = transition.reward;
+        if !transition.terminal {
+            target.feed(&transition.next);
+            let next_best = target.get_output().unwrap().into_iter().fold(f64::NEG_INFINITY, f64::max);
+            value += config.gamma * next_best;
+        }
+
+        // Só a ação jogada tem alvo novo; as outras miram na própria saída e não recebem gradiente
+        q.feed(&transition.observation);
+        let mut targets = q.get_output().unwrap();
+        targets[transition.action] = value;
+        q.backward(&transition.observation, &targets, Loss::MeanSquaredError, &mut gradients);
+    }
+    gradients.scale(1.0 / batch.len() as f64);
+    optimizer.step(q, &gradients);
+}
+
+/// Treina uma rede Q por DQN e a devolve já convertida em controlador (`greedy_head`).
+/// `on_update` recebe o resumo de cada `batch_episodes` episódios.
+pub fn train_q_network(
+    config: &RlConfig,
+    mut on_update: impl FnMut(RlStats),
+) -> Result<NeuralNetwork, Box<dyn Error>> {
+    if config.initial_network.is_some() {
+        return Err("o DQN treina uma rede Q do zero; --initial-network só vale para o REINFORCE".into());
+    }
+    let mut rng = TrainingRng::seed_from_u64(config.seed);
+    let mut q = config
+        .topology
+        .generate_with_rng(&mut rng)
+        .with_observation(config.observation.clone());
+    let mut target = q.clone();
+
+    let mut env = PongEnv::new(q.observation.clone(), config.max_steps);
+    let mut optimizer = config.optimizer.build(config.learning_rate, config.momentum);
+    let mut buffer = ReplayBuffer::new(config.dqn.replay_capacity);
+    let mut steps = 0usize;
+    let mut total_reward = 0.0;
+    let mut total_hits = 0;
+    let mut batch = 0;
+
+    for episode in 0..config.episodes {
+        let epsilon = config.dqn.epsilon(episode);
+        let mut observation = env.reset(rng.random());
+        loop {
+            let action = if rng.random_bool(epsilon) {
+                rng.random_range(0..ACTIONS.len())
+            } else {
+                greedy_action(&mut q, &observation)
+            };
+            let (next, reward, done, info) = env.step((ACTIONS[action], 1.0));
+            total_reward += reward;
+            // Interromper por `max_steps` não é fim de partida: o valor futuro continua valendo
+            buffer.push(Transition {
+                observation,
+                action,
+                reward,
+                next: next.clone(),
+                terminal: info.winner.is_some(),
+            });
+
+            steps += 1;
+            if steps >= config.dqn.learning_starts.max(config.dqn.replay_batch) {
+                learn(&mut q, &mut target, &buffer, config, optimizer.as_mut(), &mut rng);
+            }
+            if steps.is_multiple_of(config.dqn.target_update) {
+                target = q.clone();
+            }
+            if done {
+                total_hits += info.hits;
+                break;
+            }
+            observation = next;
+        }
+
+        batch += 1;
+        if batch == config.batch_episodes || episode + 1 == config.episodes {
+            on_update(RlStats {
+                episodes: episode + 1,
+                average_reward: total_reward / batch as f64,
+                average_hits: total_hits as f64 / batch as f64,
+            });
+            total_reward = 0.0;
+            total_hits = 0;
+            batch = 0;
+        }
+    }
+    Ok(greedy_head(q))
+}
+
+/// Acrescenta a `q` (saídas `[Q sobe, Q desce, Q para]`) duas camadas fixas que produzem
+/// as saídas do controlador: "sobe" passa de 0,5 só quando Q sobe é o maior valor, "desce"
+/// só quando Q desce é o maior, e a velocidade é máxima. Usa `min(a, b) = a - relu(a - b)`.
+pub fn greedy_head(q: NeuralNetwork) -> NeuralNetwork {
+    let observation = q.observation.clone();
+    let mut layers = q.layers;
+    let relu = |weights: [f64; 3]| Neuron::new_seted(weights.to_vec(), 0.0, ActivationFunction::Relu);
+    layers.push(Layer {
+        neurons: vec![
+            relu([1.0, -1.0, 0.0]), // relu(Qs - Qd)
+            relu([-1.0, 1.0, 0.0]), // relu(Qd - Qs)
+            relu([-1.0, 0.0, 1.0]), // relu(Qp - Qs)
+            relu([0.0, -1.0, 1.0]), // relu(Qp - Qd)
+        ],
+    });
+    let sigmoid = |weights: [f64; 4], bias| Neuron::new_seted(weights.to_vec(), bias, ActivationFunction::Sigmoid);
+    layers.push(Layer {
+        neurons: vec![
+            // min(Qs - Qd, Qs - Qp)
+            sigmoid([1.0, -1.0, 0.0, -1.0], 0.0),
+            // min(Qd - Qs, Qd - Qp)
+            sigmoid([-1.0, 1.0, -1.0, 0.0], 0.0),
+            sigmoid([0.0; 4], FULL_SPEED_BIAS),
+        ],
+    });
+    debug_assert_eq!(layers.last().map(|layer| layer.neurons.len()), Some(CONTROLLER_OUTPUTS));
+    NeuralNetwork::from_layers(layers).with_observation(observation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_network::neural_network_f::output_action;
+
+    /// Rede "Q" linear que só repete a entrada, para escolher os valores Q à mão.
+    fn identity_q() -> NeuralNetwork {
+        let neurons = (0..3)
+            .map(|i| {
+                let mut weights = vec![0.0; 3];
+                weights[i] = 1.0;
+                Neuron::new_seted(weights, 0.0, ActivationFunction::Linear)
+            })
+            .collect();
+        NeuralNetwork::from_layers(vec![Layer { neurons }])
+    }
+
+    #[test]
+    fn greedy_head_plays_the_best_action() {
+        let mut controller = greedy_head(identity_q());
+        let cases = [
+            ([0.9, 0.2, -0.4], PlayerDirection::Up),
+            ([0.9, 1.3, 1.2], PlayerDirection::Down),
+            ([-0.1, -0.3, 0.01], PlayerDirection::None),
+            ([2.0, 2.5, -3.0], PlayerDirection::Down),
+            ([0.51, 0.5, 0.5], PlayerDirection::Up),
+        ];
+        for (values, expected) in cases {
+            controller.feed(&values);
+            let (direction, speed) = output_action(&controller.get_output().unwrap());
+            assert_eq!(direction, expected, "valores Q {:?}", values);
+            assert!(speed > 0.99);
+        }
+    }
+
+    #[test]
+    fn replay_buffer_drops_the_oldest_transitions() {
+        let mut buffer = ReplayBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(Transition {
+                observation: vec![i as f64],
+                action: 0,
+                reward: 0.0,
+                next: Vec::new(),
+                terminal: false,
+            });
+        }
+        let kept: Vec<f64> = buffer.transitions.iter().map(|t| t.observation[0]).collect();
+        assert_eq!(kept, [2.0, 3.0, 4.0]);
+
+        let mut rng = TrainingRng::seed_from_u64(0);
+        assert!(buffer.sample(10, &mut rng).iter().all(|t| t.observation[0] >= 2.0));
+    }
+
+    #[test]
+    fn epsilon_decays_linearly() {
+        let config = DqnConfig {
+            epsilon_start: 1.0,
+            epsilon_end: 0.1,
+            epsilon_decay_episodes: 10,
+            ..DqnConfig::default()
+        };
+        assert_eq!(config.epsilon(0), 1.0);
+        assert!((config.epsilon(5) - 0.55).abs() < 1e-12);
+        assert!((config.epsilon(10) - 0.1).abs() < 1e-12);
+        assert!((config.epsilon(50) - 0.1).abs() < 1e-12);
+    }
+}
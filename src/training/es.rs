@@ -0,0 +1,413 @@
+//! Estratégias evolutivas sobre o vetor de pesos da rede (na ordem de `all_weights_mut`):
+//! OpenAI-ES (Salimans et al., 2017) e CMA-ES (Hansen, "The CMA Evolution Strategy: A Tutorial").
+//!
+//! Em vez de cruzar e mutar indivíduos, a estratégia mantém uma distribuição de pesos:
+//! cada geração é amostrada dela (`ask`) e, depois de jogada, a pontuação de cada amostra
+//! atualiza a distribuição (`tell`). Toda a população tem a topologia da rede inicial.
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::training::TrainingRng;
+
+/// Parâmetros das estratégias evolutivas (usados com `algorithm: Es` ou `CmaEs`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EsConfig {
+    /// Desvio inicial das perturbações (fixo no OpenAI-ES, adaptado pelo CMA-ES).
+    pub sigma: f64,
+    /// Taxa de aprendizado da média no OpenAI-ES.
+    pub learning_rate: f64,
+}
+
+impl Default for EsConfig {
+    fn default() -> Self {
+        EsConfig {
+            sigma: 0.1,
+            learning_rate: 0.05,
+        }
+    }
+}
+
+impl EsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sigma <= 0.0 || self.learning_rate <= 0.0 {
+            return Err("es: sigma e learning_rate devem ser positivos".into());
+        }
+        Ok(())
+    }
+}
+
+/// Uma estratégia evolutiva no formato ask/tell.
+pub trait EvolutionStrategy {
+    /// Sorteia `count` vetores de pesos da distribuição atual.
+    fn ask(&mut self, count: usize, rng: &mut TrainingRng) -> Vec<Vec<f64>>;
+    /// Atualiza a distribuição com as amostras jogadas e suas pontuações (maior é melhor).
+    fn tell(&mut self, samples: &[Vec<f64>], fitness: &[f64]);
+    /// Média atual da distribuição.
+    fn mean(&self) -> &[f64];
+}
+
+/// Estado de uma estratégia evolutiva, guardado no checkpoint junto com o treino.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EsState {
+    OpenAi(OpenAiEs),
+    Cma(CmaEs),
+}
+
+impl EsState {
+    pub fn strategy(&mut self) -> &mut dyn EvolutionStrategy {
+        match self {
+            EsState::OpenAi(es) => es,
+            EsState::Cma(es) => es,
+        }
+    }
+}
+
+/// Pesos da rede como um vetor, na ordem de `all_weights_mut`.
+pub fn flat_weights(nn: &mut NeuralNetwork) -> Vec<f64> {
+    nn.all_weights_mut().into_iter().map(|w| *w).collect()
+}
+
+/// Cópia de `template` com os pesos de `weights`.
+pub fn with_weights(template: &NeuralNetwork, weights: &[f64]) -> NeuralNetwork {
+    let mut nn = template.clone();
+    for (w, &value) in nn.all_weights_mut().into_iter().zip(weights) {
+        *w = value;
+    }
+    nn
+}
+
+fn sample_normal(n: usize, rng: &mut TrainingRng) -> Vec<f64> {
+    (0..n).map(|_| rng.sample(StandardNormal)).collect()
+}
+
+/// OpenAI-ES: perturbações gaussianas antitéticas de desvio fixo e subida de gradiente
+/// estimado a partir dos postos (ranks) das pontuações.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiEs {
+    mean: Vec<f64>,
+    sigma: f64,
+    learning_rate: f64,
+}
+
+impl OpenAiEs {
+    pub fn new(mean: Vec<f64>, config: &EsConfig) -> Self {
+        OpenAiEs {
+            mean,
+            sigma: config.sigma,
+            learning_rate: config.learning_rate,
+        }
+    }
+}
+
+impl EvolutionStrategy for OpenAiEs {
+    fn ask(&mut self, count: usize, rng: &mut TrainingRng) -> Vec<Vec<f64>> {
+        let mut samples = Vec::with_capacity(count);
+        // Pares espelhados (θ + σε, θ − σε) reduzem a variância da estimativa
+        while samples.len() < count {
+            let epsilon = sample_normal(self.mean.len(), rng);
+            let plus = self.mean.iter().zip(&epsilon).map(|(m, e)| m + self.sigma * e).collect();
+            samples.push(plus);
+            if samples.len() < count {
+                let minus = self.mean.iter().zip(&epsilon).map(|(m, e)| m - self.sigma * e).collect();
+                samples.push(minus);
+            }
+        }
+        samples
+    }
+
+    fn tell(&mut self, samples: &[Vec<f64>], fitness: &[f64]) {
+        let n = samples.len();
+        if n < 2 {
+            return;
+        }
+        // Utilidade centrada pelo posto, em [-0.5, 0.5]: imune à escala das pontuações
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| fitness[a].total_cmp(&fitness[b]));
+        let mut utility = vec![0.0; n];
+        for (rank, &i) in order.iter().enumerate() {
+            utility[i] = rank as f64 / (n - 1) as f64 - 0.5;
+        }
+
+        let mut gradient = vec![0.0; self.mean.len()];
+        for (sample, u) in samples.iter().zip(&utility) {
+            for ((g, x), m) in gradient.iter_mut().zip(sample).zip(&self.mean) {
+                // ε = (x − θ) / σ
+                *g += u * (x - m) / self.sigma;
+            }
+        }
+        let step = self.learning_rate / (n as f64 * self.sigma);
+        for (m, g) in self.mean.iter_mut().zip(&gradient) {
+            *m += step * g;
+        }
+    }
+
+    fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+}
+
+/// CMA-ES: adapta a matriz de covariância e o passo das amostras a partir das melhores
+/// metades de cada geração. Guarda só o estado dinâmico; as constantes dependem do
+/// tamanho da população e são recalculadas a cada `tell`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmaEs {
+    mean: Vec<f64>,
+    sigma: f64,
+    /// Matriz de covariância C.
+    covariance: Vec<Vec<f64>>,
+    /// Autovetores de C (colunas) e raízes dos autovalores: C = B·D²·Bᵀ.
+    eigenvectors: Vec<Vec<f64>>,
+    eigenvalues_sqrt: Vec<f64>,
+    /// Caminhos de evolução da covariância e do passo.
+    path_c: Vec<f64>,
+    path_sigma: Vec<f64>,
+    generation: i32,
+}
+
+impl CmaEs {
+    pub fn new(mean: Vec<f64>, config: &EsConfig) -> Self {
+        let n = mean.len();
+        CmaEs {
+            mean,
+            sigma: config.sigma,
+            covariance: identity(n),
+            eigenvectors: identity(n),
+            eigenvalues_sqrt: vec![1.0; n],
+            path_c: vec![0.0; n],
+            path_sigma: vec![0.0; n],
+            generation: 0,
+        }
+    }
+
+    /// B·(D ⊙ z)
+    fn transform(&self, z: &[f64]) -> Vec<f64> {
+        let scaled: Vec<f64> = z.iter().zip(&self.eigenvalues_sqrt).map(|(z, d)| z * d).collect();
+        mat_vec(&self.eigenvectors, &scaled)
+    }
+
+    /// C^(-1/2)·y = B·D⁻¹·Bᵀ·y
+    fn inverse_sqrt_times(&self, y: &[f64]) -> Vec<f64> {
+        let n = y.len();
+        let mut bt_y = vec![0.0; n];
+        for (j, value) in bt_y.iter_mut().enumerate() {
+            *value = (0..n).map(|i| self.eigenvectors[i][j] * y[i]).sum::<f64>() / self.eigenvalues_sqrt[j];
+        }
+        mat_vec(&self.eigenvectors, &bt_y)
+    }
+}
+
+impl EvolutionStrategy for CmaEs {
+    fn ask(&mut self, count: usize, rng: &mut TrainingRng) -> Vec<Vec<f64>> {
+        (0..count)
+            .map(|_| {
+                let y = self.transform(&sample_normal(self.mean.len(), rng));
+                self.mean.iter().zip(&y).map(|(m, y)| m + self.sigma * y).collect()
+            })
+            .collect()
+    }
+
+    fn tell(&mut self, samples: &[Vec<f64>], fitness: &[f64]) {
+        let n = self.mean.len();
+        let lambda = samples.len();
+        let mu = lambda / 2;
+        if mu == 0 || n == 0 {
+            return;
+        }
+        let nf = n as f64;
+
+        // Pesos de recombinação e constantes de adaptação
+        let mut weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let weight_sum: f64 = weights.iter().sum();
+        weights.iter_mut().for_each(|w| *w /= weight_sum);
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        // Desvios padronizados das melhores amostras: y = (x − m) / σ
+        let mut order: Vec<usize> = (0..lambda).collect();
+        order.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+        let best: Vec<Vec<f64>> = order[..mu]
+            .iter()
+            .map(|&k| samples[k].iter().zip(&self.mean).map(|(x, m)| (x - m) / self.sigma).collect())
+            .collect();
+        let mut y_w = vec![0.0; n];
+        for (y, w) in best.iter().zip(&weights) {
+            for (acc, v) in y_w.iter_mut().zip(y) {
+                *acc += w * v;
+            }
+        }
+
+        // Média
+        for (m, y) in self.mean.iter_mut().zip(&y_w) {
+            *m += self.sigma * y;
+        }
+
+        // Caminhos de evolução
+        self.generation += 1;
+        let c_inv_y = self.inverse_sqrt_times(&y_w);
+        let ps_factor = (cs * (2.0 - cs) * mueff).sqrt();
+        for (ps, v) in self.path_sigma.iter_mut().zip(&c_inv_y) {
+            *ps = (1.0 - cs) * *ps + ps_factor * v;
+        }
+        let ps_norm = norm(&self.path_sigma);
+        let hsig = ps_norm / (1.0 - (1.0 - cs).powi(2 * self.generation)).sqrt() / chi_n < 1.4 + 2.0 / (nf + 1.0);
+        let pc_factor = if hsig { (cc * (2.0 - cc) * mueff).sqrt() } else { 0.0 };
+        for (pc, y) in self.path_c.iter_mut().zip(&y_w) {
+            *pc = (1.0 - cc) * *pc + pc_factor * y;
+        }
+
+        // Covariância: termo de posto 1 (caminho) + termo de posto µ (melhores amostras)
+        let hsig_correction = if hsig { 0.0 } else { c1 * cc * (2.0 - cc) };
+        for i in 0..n {
+            for j in 0..=i {
+                let rank_mu: f64 = best.iter().zip(&weights).map(|(y, w)| w * y[i] * y[j]).sum();
+                let value = (1.0 - c1 - cmu + hsig_correction) * self.covariance[i][j]
+                    + c1 * self.path_c[i] * self.path_c[j]
+                    + cmu * rank_mu;
+                self.covariance[i][j] = value;
+                self.covariance[j][i] = value;
+            }
+        }
+
+        // Passo
+        self.sigma *= ((cs / damps) * (ps_norm / chi_n - 1.0)).exp();
+
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&self.covariance);
+        self.eigenvalues_sqrt = eigenvalues.iter().map(|&v| v.max(1e-20).sqrt()).collect();
+        self.eigenvectors = eigenvectors;
+    }
+
+    fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+fn mat_vec(matrix: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    matrix.iter().map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum()).collect()
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Autovalores e autovetores (em colunas) de uma matriz simétrica, pelo método de Jacobi.
+/// As redes do jogo têm poucas dezenas de pesos, então o custo O(n³) por varredura é pequeno.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut v = identity(n);
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-22 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                // Rotação que zera a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let akp = row[p];
+                    let akq = row[q];
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                for (k, (apk, aqk)) in row_p.into_iter().zip(row_q).enumerate() {
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for row in v.iter_mut() {
+                    let vkp = row[p];
+                    let vkq = row[q];
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const OPTIMUM: [f64; 4] = [1.0, -2.0, 0.5, 3.0];
+
+    /// Aptidão quadrática com máximo em `OPTIMUM`.
+    fn fitness(weights: &[f64]) -> f64 {
+        -weights.iter().zip(OPTIMUM).map(|(w, o)| (w - o).powi(2)).sum::<f64>()
+    }
+
+    /// Roda `generations` de ask/tell e retorna a aptidão da média no início e no fim.
+    fn optimize(strategy: &mut dyn EvolutionStrategy, generations: usize) -> (f64, f64) {
+        let mut rng = TrainingRng::seed_from_u64(8);
+        let before = fitness(strategy.mean());
+        for _ in 0..generations {
+            let samples = strategy.ask(20, &mut rng);
+            let scores: Vec<f64> = samples.iter().map(|sample| fitness(sample)).collect();
+            strategy.tell(&samples, &scores);
+        }
+        (before, fitness(strategy.mean()))
+    }
+
+    #[test]
+    fn openai_es_climbs_a_quadratic() {
+        let config = EsConfig { sigma: 0.1, learning_rate: 0.1 };
+        let (before, after) = optimize(&mut OpenAiEs::new(vec![0.0; 4], &config), 300);
+        assert!(after > before / 10.0, "{} -> {}", before, after);
+    }
+
+    #[test]
+    fn cma_es_climbs_a_quadratic() {
+        let (before, after) = optimize(&mut CmaEs::new(vec![0.0; 4], &EsConfig::default()), 100);
+        assert!(after > -1e-3, "{} -> {}", before, after);
+    }
+
+    #[test]
+    fn nan_fitness_does_not_panic() {
+        let config = EsConfig::default();
+        let strategies: [Box<dyn EvolutionStrategy>; 2] = [
+            Box::new(OpenAiEs::new(vec![0.0; 4], &config)),
+            Box::new(CmaEs::new(vec![0.0; 4], &config)),
+        ];
+        let mut rng = TrainingRng::seed_from_u64(9);
+        for mut strategy in strategies {
+            let samples = strategy.ask(6, &mut rng);
+            let mut scores: Vec<f64> = samples.iter().map(|sample| fitness(sample)).collect();
+            scores[2] = f64::NAN;
+            strategy.tell(&samples, &scores);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Aptidão com vários objetivos: cada episódio acumula suas métricas (`MatchMetrics`) e a
+//! aptidão dele é a soma delas ponderada pelos pesos de `FitnessConfig`. A aptidão da rede
+//! agrega a dos seus episódios (`FitnessAggregation`).
+//!
+//! Os pesos padrão contam só as rebatidas, a aptidão original do treino. Pesos negativos
+//! penalizam: `travel_distance` negativo, por exemplo, desestimula redes que tremem no lugar.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::pong::FIXED_DT;
+
+/// Métricas do jogador 1 em um episódio (uma partida).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct MatchMetrics {
+    /// Rebatidas do jogador.
+    pub hits: i32,
+    /// Rebatidas do adversário.
+    pub opponent_hits: i32,
+    /// Maior rally, em rebatidas dos dois jogadores.
+    pub longest_rally: i32,
+    pub points_won: i32,
+    pub points_conceded: i32,
+    /// 1 se a partida foi vencida, 0 se perdida ou interrompida por `max_steps`.
+    pub wins: i32,
+    /// Distância vertical percorrida pela raquete, em unidades.
+    pub travel_distance: f64,
+    /// Passos de simulação jogados.
+    pub steps: usize,
+}
+
+impl MatchMetrics {
+    /// Tempo jogado, em segundos de simulação.
+    pub fn time_survived(&self) -> f64 {
+        self.steps as f64 * FIXED_DT as f64
+    }
+}
+
+/// Pesos de cada objetivo na aptidão.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FitnessConfig {
+    /// Por rebatida.
+    pub hits: f64,
+    /// Por rebatida do maior rally.
+    pub rally_length: f64,
+    /// Por ponto marcado.
+    pub points_won: f64,
+    /// Por ponto sofrido; use um peso negativo para penalizar.
+    pub points_conceded: f64,
+    /// Por unidade percorrida pela raquete; use um peso negativo para penalizar.
+    pub travel_distance: f64,
+    /// Por segundo jogado.
+    pub time_survived: f64,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        FitnessConfig {
+            hits: 1.0,
+            rally_length: 0.0,
+            points_won: 0.0,
+            points_conceded: 0.0,
+            travel_distance: 0.0,
+            time_survived: 0.0,
+        }
+    }
+}
+
+impl FitnessConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.hits,
+            self.rally_length,
+            self.points_won,
+            self.points_conceded,
+            self.travel_distance,
+            self.time_survived,
+        ];
+        if weights.iter().any(|weight| !weight.is_finite()) {
+            return Err("fitness: os pesos devem ser números finitos".into());
+        }
+        Ok(())
+    }
+
+    /// Aptidão das métricas `metrics`.
+    pub fn score(&self, metrics: &MatchMetrics) -> f64 {
+        self.hits * metrics.hits as f64
+            + self.rally_length * metrics.longest_rally as f64
+            + self.points_won * metrics.points_won as f64
+            + self.points_conceded * metrics.points_conceded as f64
+            + self.travel_distance * metrics.travel_distance
+            + self.time_survived * metrics.time_survived()
+    }
+}
+
+/// Como a aptidão dos episódios de uma rede vira a aptidão dela.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FitnessAggregation {
+    Mean,
+    Median,
+    /// O pior episódio: favorece redes consistentes em vez das que tiveram sorte.
+    Worst,
+}
+
+impl FromStr for FitnessAggregation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mean" => Ok(FitnessAggregation::Mean),
+            "median" => Ok(FitnessAggregation::Median),
+            "worst" => Ok(FitnessAggregation::Worst),
+            _ => Err(format!("agregação desconhecida: {} (use mean, median ou worst)", s)),
+        }
+    }
+}
+
+impl FitnessAggregation {
+    /// Agrega `scores`, que não é vazio.
+    pub fn aggregate(self, scores: &[f64]) -> f64 {
+        match self {
+            FitnessAggregation::Mean => mean(scores),
+            FitnessAggregation::Median => {
+                let mut sorted = scores.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                let middle = sorted.len() / 2;
+                match sorted.len() % 2 {
+                    0 => (sorted[middle - 1] + sorted[middle]) / 2.0,
+                    _ => sorted[middle],
+                }
+            }
+            FitnessAggregation::Worst => scores.iter().copied().fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Variância (populacional) de `values`.
+pub fn variance(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregations_on_known_scores() {
+        let odd = [7.0, -2.0, 4.0, 10.0, 1.0];
+        assert_eq!(FitnessAggregation::Mean.aggregate(&odd), 4.0);
+        assert_eq!(FitnessAggregation::Median.aggregate(&odd), 4.0);
+        assert_eq!(FitnessAggregation::Worst.aggregate(&odd), -2.0);
+
+        let even = [3.0, 9.0, 1.0, 5.0];
+        assert_eq!(FitnessAggregation::Median.aggregate(&even), 4.0);
+        assert_eq!(FitnessAggregation::Worst.aggregate(&even), 1.0);
+
+        for aggregation in [FitnessAggregation::Mean, FitnessAggregation::Median, FitnessAggregation::Worst] {
+            assert_eq!(aggregation.aggregate(&[2.5]), 2.5);
+        }
+    }
+
+    #[test]
+    fn variance_is_the_population_variance() {
+        assert_eq!(variance(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]), 4.0);
+        assert_eq!(variance(&[3.0]), 0.0);
+    }
+
+    #[test]
+    fn score_weights_each_metric() {
+        let metrics = MatchMetrics {
+            hits: 4,
+            opponent_hits: 7,
+            longest_rally: 9,
+            points_won: 2,
+            points_conceded: 3,
+            wins: 0,
+            travel_distance: 250.0,
+            steps: 180,
+        };
+        assert_eq!(metrics.time_survived(), 180.0 * FIXED_DT as f64);
+        assert_eq!(FitnessConfig::default().score(&metrics), 4.0);
+
+        let weights = FitnessConfig {
+            hits: 1.0,
+            rally_length: 0.5,
+            points_won: 10.0,
+            points_conceded: -5.0,
+            travel_distance: -0.01,
+            time_survived: 2.0,
+        };
+        // 4 + 4.5 + 20 - 15 - 2.5 + 2 * 3s
+        let expected = 4.0 + 4.5 + 20.0 - 15.0 - 2.5 + 2.0 * metrics.time_survived();
+        assert!((weights.score(&metrics) - expected).abs() < 1e-9);
+        assert!((expected - 17.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,183 @@
+//! Treino por self-play contra uma liga de campeões.
+//!
+//! Com `opponent: League`, cada episódio de uma rede é uma partida contra um campeão
+//! sorteado da liga, no lado direito do campo (ver `MirroredController`). A aptidão de cada
+//! episódio ganha `WIN_SCORE` pela vitória mais a margem de pontos da partida. A cada
+//! `promote_every` gerações a melhor rede entra na liga se tiver saldo positivo contra ela;
+//! os campeões mais antigos saem quando a liga passa de `pool_size`.
+//!
+//! Enquanto a liga está vazia (sem `initial_network`) os jogos são contra o `BotController`,
+//! e a melhor rede da primeira geração vira o primeiro campeão.
+
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::training::fitness::MatchMetrics;
+use crate::training::TrainingRng;
+
+/// Pontos de aptidão por partida vencida, somados à margem da partida.
+pub const WIN_SCORE: i32 = 3;
+
+/// Contra quem a população joga no treino.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OpponentKind {
+    /// O `BotController` fixo; a aptidão é a quantidade de rebatidas.
+    Bot,
+    /// Campeões de gerações anteriores (self-play).
+    League,
+}
+
+impl FromStr for OpponentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bot" => Ok(OpponentKind::Bot),
+            "league" => Ok(OpponentKind::League),
+            _ => Err(format!("adversário desconhecido: {} (use bot ou league)", s)),
+        }
+    }
+}
+
+/// Parâmetros da liga (usados com `opponent: League`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeagueConfig {
+    /// Máximo de campeões guardados.
+    pub pool_size: usize,
+    /// A cada quantas gerações a melhor rede pode ser promovida.
+    pub promote_every: usize,
+}
+
+impl Default for LeagueConfig {
+    fn default() -> Self {
+        LeagueConfig {
+            pool_size: 8,
+            promote_every: 1,
+        }
+    }
+}
+
+impl LeagueConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pool_size == 0 || self.promote_every == 0 {
+            return Err("league: pool_size e promote_every devem ser maiores que zero".into());
+        }
+        Ok(())
+    }
+}
+
+/// Os campeões, do mais antigo para o mais novo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct League {
+    pub champions: Vec<NeuralNetwork>,
+}
+
+impl League {
+    pub fn is_empty(&self) -> bool {
+        self.champions.is_empty()
+    }
+
+    /// Sorteia, com reposição, os `count` adversários de uma rede.
+    pub fn sample(&self, count: usize, rng: &mut TrainingRng) -> Vec<NeuralNetwork> {
+        if self.champions.is_empty() {
+            return Vec::new();
+        }
+        (0..count)
+            .map(|_| self.champions[rng.random_range(0..self.champions.len())].clone())
+            .collect()
+    }
+
+    /// Promove `champion` se a liga estiver vazia ou se `fitness` (a aptidão dele contra
+    /// a liga) for positiva. Retorna se houve promoção.
+    pub fn promote(&mut self, champion: &NeuralNetwork, fitness: i32, config: &LeagueConfig) -> bool {
+        if !self.champions.is_empty() && fitness <= 0 {
+            return false;
+        }
+        self.champions.push(champion.clone());
+        if self.champions.len() > config.pool_size {
+            self.champions.remove(0);
+        }
+        true
+    }
+}
+
+/// Placar da liga de um episódio: `WIN_SCORE` pela vitória mais a margem de pontos.
+/// Numa partida interrompida por `max_steps` só a margem conta.
+pub fn match_score(metrics: &MatchMetrics) -> i32 {
+    WIN_SCORE * metrics.wins + metrics.points_won - metrics.points_conceded
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::neural_network::neuron::ActivationFunction;
+
+    /// Rede identificável pelo bias do primeiro neurônio.
+    fn network(id: f64) -> NeuralNetwork {
+        let mut rng = TrainingRng::seed_from_u64(0);
+        let mut nn = NeuralNetwork::new_with_rng(4, &[3], &[ActivationFunction::Sigmoid], &mut rng).unwrap();
+        nn.layers[0].neurons[0].bias = id;
+        nn
+    }
+
+    fn ids(league: &League) -> Vec<f64> {
+        league.champions.iter().map(|nn| nn.layers[0].neurons[0].bias).collect()
+    }
+
+    #[test]
+    fn empty_league_always_accepts() {
+        let config = LeagueConfig::default();
+        let mut league = League::default();
+        assert!(league.promote(&network(1.0), -5, &config));
+        assert_eq!(ids(&league), [1.0]);
+    }
+
+    #[test]
+    fn promotion_needs_a_positive_score_against_the_league() {
+        let config = LeagueConfig::default();
+        let mut league = League::default();
+        league.promote(&network(1.0), 0, &config);
+        assert!(!league.promote(&network(2.0), 0, &config));
+        assert!(!league.promote(&network(3.0), -1, &config));
+        assert!(league.promote(&network(4.0), 1, &config));
+        assert_eq!(ids(&league), [1.0, 4.0]);
+    }
+
+    #[test]
+    fn oldest_champion_leaves_past_pool_size() {
+        let config = LeagueConfig {
+            pool_size: 3,
+            ..LeagueConfig::default()
+        };
+        let mut league = League::default();
+        for id in 1..=5 {
+            league.promote(&network(f64::from(id)), 1, &config);
+        }
+        assert_eq!(ids(&league), [3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn match_score_is_wins_plus_margin() {
+        let won = MatchMetrics {
+            wins: 1,
+            points_won: 5,
+            points_conceded: 2,
+            ..MatchMetrics::default()
+        };
+        assert_eq!(match_score(&won), WIN_SCORE + 3);
+
+        let lost = MatchMetrics {
+            wins: 0,
+            points_won: 1,
+            points_conceded: 5,
+            ..MatchMetrics::default()
+        };
+        assert_eq!(match_score(&lost), -4);
+    }
+}
@@ -0,0 +1,19 @@
+use rand_chacha::ChaCha8Rng;
+
+pub mod checkpoint;
+pub mod cloning;
+pub mod config;
+pub mod crossover;
+pub mod dqn;
+pub mod es;
+pub mod fitness;
+pub mod league;
+pub mod mutation;
+pub mod neat;
+pub mod population;
+pub mod rl;
+pub mod selection;
+
+/// Gerador de toda a aleatoriedade do treino: redes iniciais, mutações e as sementes
+/// de cada partida. Com a mesma semente o treino inteiro se repete.
+pub type TrainingRng = ChaCha8Rng;
\ No newline at end of file
@@ -0,0 +1,252 @@
+//! Operadores de mutação aplicados a cada nova rede depois da seleção (e do cruzamento).
+
+use std::str::FromStr;
+
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::training::config::TrainingConfig;
+use crate::training::population::GenerationStats;
+use crate::training::TrainingRng;
+
+/// Altera os parâmetros de uma rede recém-gerada.
+pub trait Mutation {
+    fn mutate(&self, nn: &mut NeuralNetwork, rng: &mut TrainingRng);
+}
+
+/// Mutações disponíveis na configuração. Todas usam `mutation_rate` como a probabilidade
+/// de cada parâmetro ser alterado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MutationKind {
+    /// Soma um valor uniforme em ±`mutation_magnitude`.
+    Uniform,
+    /// Soma um valor normal com desvio `mutation_magnitude`.
+    Gaussian,
+    /// Sorteia o parâmetro de novo, como na criação da rede.
+    Reset,
+    /// Só os bias recebem a perturbação normal; os pesos ficam intactos.
+    BiasOnly,
+    /// Perturbação normal com desvio ajustado pelo progresso do treino (ver `adaptive_sigma`).
+    Adaptive,
+}
+
+impl MutationKind {
+    /// Cria a mutação com os parâmetros de `config`. `history` é usado pela `Adaptive`.
+    pub fn build(self, config: &TrainingConfig, history: &[GenerationStats]) -> Box<dyn Mutation> {
+        let rate = config.mutation_rate;
+        match self {
+            MutationKind::Uniform => Box::new(UniformMutation {
+                rate,
+                magnitude: config.mutation_magnitude,
+            }),
+            MutationKind::Gaussian => Box::new(GaussianMutation {
+                rate,
+                sigma: config.mutation_magnitude,
+                bias_only: false,
+            }),
+            MutationKind::Reset => Box::new(ResetMutation { rate }),
+            MutationKind::BiasOnly => Box::new(GaussianMutation {
+                rate,
+                sigma: config.mutation_magnitude,
+                bias_only: true,
+            }),
+            MutationKind::Adaptive => Box::new(GaussianMutation {
+                rate,
+                sigma: adaptive_sigma(config, history),
+                bias_only: false,
+            }),
+        }
+    }
+}
+
+impl FromStr for MutationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(MutationKind::Uniform),
+            "gaussian" => Ok(MutationKind::Gaussian),
+            "reset" => Ok(MutationKind::Reset),
+            "bias-only" | "biasonly" => Ok(MutationKind::BiasOnly),
+            "adaptive" => Ok(MutationKind::Adaptive),
+            _ => Err(format!(
+                "mutação desconhecida: {} (use uniform, gaussian, reset, bias-only ou adaptive)",
+                s
+            )),
+        }
+    }
+}
+
+/// Desvio da mutação `Adaptive`, calculado a partir do histórico do treino: começa em
+/// `mutation_magnitude`, encolhe por `mutation_annealing` a cada geração em que a melhor
+/// pontuação subiu (refinando o que está dando certo) e cresce na mesma proporção quando
+/// estagna (para escapar do platô), sempre entre `mutation_min_magnitude` e `mutation_magnitude`.
+/// Como depende só do histórico, um treino retomado de checkpoint continua com o mesmo desvio.
+pub fn adaptive_sigma(config: &TrainingConfig, history: &[GenerationStats]) -> f64 {
+    let max = config.mutation_magnitude;
+    let min = config.mutation_min_magnitude.min(max);
+    let mut sigma = max;
+    let mut best_so_far = f64::NEG_INFINITY;
+    for stats in history {
+        if stats.best > best_so_far {
+            sigma *= config.mutation_annealing;
+            best_so_far = stats.best;
+        } else {
+            sigma /= config.mutation_annealing;
+        }
+        sigma = sigma.clamp(min, max);
+    }
+    sigma
+}
+
+/// Aplica `delta` aos parâmetros sorteados com probabilidade `rate`, usando
+/// `Neuron::mutate_weight`/`mutate_bias`. Com `bias_only` os pesos não são tocados.
+fn perturb(
+    nn: &mut NeuralNetwork,
+    rng: &mut TrainingRng,
+    rate: f64,
+    bias_only: bool,
+    mut delta: impl FnMut(&mut TrainingRng) -> f64,
+) {
+    for layer in nn.layers.iter_mut() {
+        for neuron in layer.neurons.iter_mut() {
+            if !bias_only {
+                for i in 0..neuron.weights.len() {
+                    if rng.random::<f64>() < rate {
+                        let d = delta(rng);
+                        neuron.mutate_weight(i, d);
+                    }
+                }
+            }
+            if rng.random::<f64>() < rate {
+                let d = delta(rng);
+                neuron.mutate_bias(d);
+            }
+        }
+    }
+}
+
+pub struct UniformMutation {
+    pub rate: f64,
+    pub magnitude: f64,
+}
+
+impl Mutation for UniformMutation {
+    fn mutate(&self, nn: &mut NeuralNetwork, rng: &mut TrainingRng) {
+        let magnitude = self.magnitude;
+        perturb(nn, rng, self.rate, false, |rng| rng.random_range(-magnitude..=magnitude));
+    }
+}
+
+pub struct GaussianMutation {
+    pub rate: f64,
+    pub sigma: f64,
+    pub bias_only: bool,
+}
+
+impl Mutation for GaussianMutation {
+    fn mutate(&self, nn: &mut NeuralNetwork, rng: &mut TrainingRng) {
+        // Um desvio inválido (NaN, infinito ou negativo) não passa por `TrainingConfig::validate`;
+        // se chegar aqui mesmo assim, a rede fica como está em vez de derrubar o treino
+        if let Ok(normal) = Normal::new(0.0, self.sigma) {
+            perturb(nn, rng, self.rate, self.bias_only, |rng| normal.sample(rng));
+        }
+    }
+}
+
+pub struct ResetMutation {
+    pub rate: f64,
+}
+
+impl Mutation for ResetMutation {
+    fn mutate(&self, nn: &mut NeuralNetwork, rng: &mut TrainingRng) {
+        for weight in nn.all_weights_mut() {
+            if rng.random::<f64>() < self.rate {
+                // Mesmo intervalo usado em `Neuron::new_with_rng`
+                *weight = rng.random_range(-1.0..=1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::neural_network::neuron::ActivationFunction;
+
+    /// Histórico com a melhor aptidão de cada geração dada por `bests`.
+    fn history(bests: &[f64]) -> Vec<GenerationStats> {
+        bests
+            .iter()
+            .enumerate()
+            .map(|(generation, &best)| GenerationStats {
+                generation,
+                average: best,
+                best,
+                worst: best,
+                variance: 0.0,
+                best_variance: 0.0,
+                species: 0,
+                champions: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adaptive_sigma_shrinks_on_improvement_and_grows_on_stagnation() {
+        let config = TrainingConfig::default();
+        let max = config.mutation_magnitude;
+        assert_eq!(adaptive_sigma(&config, &[]), max);
+
+        let improving = adaptive_sigma(&config, &history(&[1.0, 2.0, 3.0]));
+        assert!((improving - max * config.mutation_annealing.powi(3)).abs() < 1e-12);
+
+        let stalled = adaptive_sigma(&config, &history(&[1.0, 2.0, 3.0, 3.0]));
+        assert!(stalled > improving);
+        assert!((stalled - improving / config.mutation_annealing).abs() < 1e-12);
+    }
+
+    #[test]
+    fn adaptive_sigma_stays_within_bounds() {
+        let config = TrainingConfig::default();
+        let rising: Vec<f64> = (0..200).map(f64::from).collect();
+        assert_eq!(adaptive_sigma(&config, &history(&rising)), config.mutation_min_magnitude);
+        let flat = vec![1.0; 200];
+        assert_eq!(adaptive_sigma(&config, &history(&flat)), config.mutation_magnitude);
+    }
+
+    #[test]
+    fn magnitudes_must_be_finite_and_non_negative() {
+        for magnitude in [f64::NAN, f64::INFINITY, -0.1] {
+            let config = TrainingConfig {
+                mutation_magnitude: magnitude,
+                ..TrainingConfig::default()
+            };
+            assert!(config.validate().is_err(), "{}", magnitude);
+            let config = TrainingConfig {
+                mutation_min_magnitude: magnitude,
+                ..TrainingConfig::default()
+            };
+            assert!(config.validate().is_err(), "{}", magnitude);
+        }
+    }
+
+    #[test]
+    fn gaussian_with_an_invalid_sigma_leaves_the_network_alone() {
+        let mut rng = TrainingRng::seed_from_u64(2);
+        let mut nn = NeuralNetwork::new_with_rng(4, &[3], &[ActivationFunction::Sigmoid], &mut rng).unwrap();
+        let before: Vec<f64> = nn.all_weights_mut().into_iter().map(|w| *w).collect();
+        let mutation = GaussianMutation {
+            rate: 1.0,
+            sigma: f64::NAN,
+            bias_only: false,
+        };
+        mutation.mutate(&mut nn, &mut rng);
+        let after: Vec<f64> = nn.all_weights_mut().into_iter().map(|w| *w).collect();
+        assert_eq!(after, before);
+    }
+}
@@ -0,0 +1,363 @@
+//! Neuroevolução no estilo NEAT: além dos pesos, as mutações mudam a estrutura da rede
+//! (neurônios, camadas e funções de ativação), e a população é dividida em espécies
+//! para que estruturas novas tenham tempo de amadurecer antes de competir com as antigas.
+//!
+//! As redes aqui são em camadas, então não há números de inovação como no NEAT original:
+//! duas redes são comparadas alinhando camadas e neurônios pela posição.
+
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::neural_network::neural_network_f::NeuralNetwork;
+use crate::neural_network::neuron::ActivationFunction;
+use crate::training::config::TrainingConfig;
+use crate::training::population::GenerationStats;
+use crate::training::TrainingRng;
+
+/// Ativações sorteadas pela mutação de ativação.
+const ACTIVATIONS: [ActivationFunction; 4] = [
+    ActivationFunction::Sigmoid,
+    ActivationFunction::Relu,
+    ActivationFunction::Tanh,
+    ActivationFunction::Linear,
+];
+
+/// Teto de `max_hidden_layers`: acima disso a profundidade fica na prática sem limite, e cada
+/// camada identidade nova só deixa a rede mais lenta sem mudar o que ela calcula.
+pub const HIDDEN_LAYERS_LIMIT: usize = 16;
+
+/// Parâmetros da evolução de topologia (usados com `algorithm: Neat`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NeatConfig {
+    /// Probabilidade de um filho ganhar um neurônio numa camada oculta.
+    pub add_neuron_rate: f64,
+    /// Probabilidade de um filho perder um neurônio de uma camada oculta.
+    pub remove_neuron_rate: f64,
+    /// Probabilidade de um filho ganhar uma camada oculta.
+    pub add_layer_rate: f64,
+    /// Probabilidade de um filho perder uma camada oculta.
+    pub remove_layer_rate: f64,
+    /// Probabilidade de um neurônio oculto do filho trocar de função de ativação.
+    pub activation_rate: f64,
+    /// Limite de neurônios por camada oculta.
+    pub max_layer_size: usize,
+    /// Limite de camadas ocultas.
+    pub max_hidden_layers: usize,
+    /// Distância abaixo da qual uma rede entra na espécie de um representante.
+    pub compatibility_threshold: f64,
+    /// Peso das diferenças estruturais (camadas, neurônios e ativações) na distância.
+    pub structure_coefficient: f64,
+    /// Peso da diferença média entre os pesos alinhados na distância.
+    pub weight_coefficient: f64,
+}
+
+impl Default for NeatConfig {
+    fn default() -> Self {
+        NeatConfig {
+            add_neuron_rate: 0.05,
+            remove_neuron_rate: 0.03,
+            add_layer_rate: 0.01,
+            remove_layer_rate: 0.01,
+            activation_rate: 0.02,
+            max_layer_size: 32,
+            max_hidden_layers: 4,
+            compatibility_threshold: 3.0,
+            structure_coefficient: 1.0,
+            weight_coefficient: 0.5,
+        }
+    }
+}
+
+impl NeatConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let rates = [
+            self.add_neuron_rate,
+            self.remove_neuron_rate,
+            self.add_layer_rate,
+            self.remove_layer_rate,
+            self.activation_rate,
+        ];
+        if rates.iter().any(|rate| !(0.0..=1.0).contains(rate)) {
+            return Err("neat: as probabilidades de mutação estrutural devem estar em [0, 1]".into());
+        }
+        if self.max_layer_size == 0 {
+            return Err("neat: max_layer_size deve ser maior que zero".into());
+        }
+        if !(1..=HIDDEN_LAYERS_LIMIT).contains(&self.max_hidden_layers) {
+            return Err(format!(
+                "neat: max_hidden_layers deve estar entre 1 e {}",
+                HIDDEN_LAYERS_LIMIT
+            ));
+        }
+        if self.compatibility_threshold <= 0.0 {
+            return Err("neat: compatibility_threshold deve ser positivo".into());
+        }
+        Ok(())
+    }
+}
+
+/// Distância de compatibilidade entre duas redes: diferenças de estrutura mais a diferença
+/// média entre os pesos que existem nas duas.
+pub fn compatibility_distance(a: &NeuralNetwork, b: &NeuralNetwork, config: &NeatConfig) -> f64 {
+    let mut structural = a.layers.len().abs_diff(b.layers.len()) as f64;
+    let mut weight_difference = 0.0;
+    let mut aligned_weights = 0;
+
+    for (layer_a, layer_b) in a.layers.iter().zip(&b.layers) {
+        structural += layer_a.neurons.len().abs_diff(layer_b.neurons.len()) as f64;
+        for (neuron_a, neuron_b) in layer_a.neurons.iter().zip(&layer_b.neurons) {
+            if neuron_a.activation_function != neuron_b.activation_function {
+                structural += 1.0;
+            }
+            let pairs = neuron_a
+                .weights
+                .iter()
+                .chain(std::iter::once(&neuron_a.bias))
+                .zip(neuron_b.weights.iter().chain(std::iter::once(&neuron_b.bias)));
+            for (wa, wb) in pairs {
+                weight_difference += (wa - wb).abs();
+                aligned_weights += 1;
+            }
+        }
+    }
+
+    let mean_weight_difference = weight_difference / aligned_weights.max(1) as f64;
+    config.structure_coefficient * structural + config.weight_coefficient * mean_weight_difference
+}
+
+/// Divide a população em espécies. Cada rede entra na primeira espécie cujo representante
+/// (o primeiro membro) está a menos de `compatibility_threshold`; senão funda uma nova.
+/// Como `networks` vem ordenada da melhor para a pior, cada espécie também fica ordenada
+/// e seu representante é o seu melhor membro.
+pub fn speciate(networks: &[NeuralNetwork], config: &NeatConfig) -> Vec<Vec<usize>> {
+    let mut species: Vec<Vec<usize>> = Vec::new();
+    for (i, nn) in networks.iter().enumerate() {
+        let existing = species.iter_mut().find(|members| {
+            compatibility_distance(&networks[members[0]], nn, config) < config.compatibility_threshold
+        });
+        match existing {
+            Some(members) => members.push(i),
+            None => species.push(vec![i]),
+        }
+    }
+    species
+}
+
+/// Mutações estruturais de um filho, cada uma com sua probabilidade em `config`.
+pub fn mutate_structure(nn: &mut NeuralNetwork, config: &NeatConfig, rng: &mut TrainingRng) {
+    let hidden_layers = nn.layers.len() - 1;
+
+    if rng.random_bool(config.add_neuron_rate) {
+        let candidates: Vec<usize> = (0..hidden_layers)
+            .filter(|&i| nn.layers[i].neurons.len() < config.max_layer_size)
+            .collect();
+        if let Some(&layer) = candidates.choose(rng) {
+            nn.add_neuron(layer, rng);
+        }
+    }
+
+    if rng.random_bool(config.remove_neuron_rate) {
+        let candidates: Vec<usize> = (0..hidden_layers)
+            .filter(|&i| nn.layers[i].neurons.len() > 1)
+            .collect();
+        if let Some(&layer) = candidates.choose(rng) {
+            let index = rng.random_range(0..nn.layers[layer].neurons.len());
+            nn.remove_neuron(layer, index);
+        }
+    }
+
+    if rng.random_bool(config.add_layer_rate) && nn.layers.len() - 1 < config.max_hidden_layers {
+        // A camada identidade tem o tamanho da sua entrada; só entra se couber no limite
+        let candidates: Vec<usize> = (0..nn.layers.len())
+            .filter(|&i| nn.layer_input_size(i) <= config.max_layer_size)
+            .collect();
+        if let Some(&index) = candidates.choose(rng) {
+            nn.add_identity_layer(index);
+        }
+    }
+
+    if rng.random_bool(config.remove_layer_rate) && nn.layers.len() > 1 {
+        let index = rng.random_range(0..nn.layers.len() - 1);
+        nn.remove_layer(index);
+    }
+
+    // A camada de saída mantém a ativação, já que o controlador interpreta suas saídas
+    let output_layer = nn.layers.len() - 1;
+    for layer in nn.layers[..output_layer].iter_mut() {
+        for neuron in layer.neurons.iter_mut() {
+            if rng.random_bool(config.activation_rate) {
+                neuron.activation_function = *ACTIVATIONS.choose(rng).unwrap();
+            }
+        }
+    }
+}
+
+/// Divide `total` filhos entre as espécies proporcionalmente a `shares`, pelo método dos maiores restos.
+fn allocate_offspring(shares: &[f64], total: usize) -> Vec<usize> {
+    let sum: f64 = shares.iter().sum();
+    let exact: Vec<f64> = shares.iter().map(|share| share / sum * total as f64).collect();
+    let mut counts: Vec<usize> = exact.iter().map(|x| x.floor() as usize).collect();
+
+    let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let ra = exact[a] - exact[a].floor();
+        let rb = exact[b] - exact[b].floor();
+        rb.total_cmp(&ra)
+    });
+    let missing = total - counts.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(missing) {
+        counts[i] += 1;
+    }
+    counts
+}
+
+/// Gera `count` filhos a partir da população `networks`, ordenada da melhor para a pior
+/// com aptidões `fitness`. Cada espécie recebe filhos proporcionais à sua aptidão média
+/// (aptidão compartilhada), e pais e cruzamentos ficam dentro da mesma espécie.
+/// Retorna os filhos e a quantidade de espécies.
+pub fn breed(
+    networks: &[NeuralNetwork],
+    fitness: &[f64],
+    count: usize,
+    config: &TrainingConfig,
+    history: &[GenerationStats],
+    rng: &mut TrainingRng,
+) -> (Vec<NeuralNetwork>, usize) {
+    let species = speciate(networks, &config.neat);
+    let selection = config.selection.build(config);
+    let mutation = config.mutation.build(config, history);
+
+    // Aptidão deslocada para começar em zero, dividida pelo tamanho da espécie
+    let worst = fitness.iter().copied().fold(f64::INFINITY, f64::min);
+    let mut shares: Vec<f64> = species
+        .iter()
+        .map(|members| members.iter().map(|&i| fitness[i] - worst).sum::<f64>() / members.len() as f64)
+        .collect();
+    // Sem aptidão utilizável (toda igual, ou NaN), os filhos são divididos pelo tamanho da espécie
+    let total: f64 = shares.iter().sum();
+    if !total.is_finite() || total <= 0.0 {
+        shares = species.iter().map(|members| members.len() as f64).collect();
+    }
+
+    let mut children = Vec::with_capacity(count);
+    for (members, offspring) in species.iter().zip(allocate_offspring(&shares, count)) {
+        let member_fitness: Vec<f64> = members.iter().map(|&i| fitness[i]).collect();
+        for _ in 0..offspring {
+            let parent = members[selection.select(&member_fitness, rng)];
+            let mut child = networks[parent].clone();
+
+            if config.crossover_rate > 0.0 && rng.random_bool(config.crossover_rate) {
+                let other = members[selection.select(&member_fitness, rng)];
+                child = config.crossover.crossover(&child, &networks[other], rng);
+            }
+
+            mutation.mutate(&mut child, rng);
+            mutate_structure(&mut child, &config.neat, rng);
+            children.push(child);
+        }
+    }
+    (children, species.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn validate_bounds_max_hidden_layers() {
+        let mut config = NeatConfig::default();
+        assert!(config.validate().is_ok());
+        config.max_hidden_layers = 0;
+        assert!(config.validate().is_err());
+        config.max_hidden_layers = HIDDEN_LAYERS_LIMIT + 1;
+        assert!(config.validate().is_err());
+    }
+
+    fn network(rng: &mut TrainingRng) -> NeuralNetwork {
+        NeuralNetwork::new_with_rng(
+            4,
+            &[5, 3],
+            &[ActivationFunction::Tanh, ActivationFunction::Sigmoid],
+            rng,
+        )
+        .unwrap()
+    }
+
+    fn outputs(nn: &mut NeuralNetwork) -> Vec<Vec<f64>> {
+        let inputs = [[0.3, -1.2, 0.8, 0.0], [-0.5, 0.1, 2.0, -0.7], [1.0, 1.0, -1.0, 0.4]];
+        inputs
+            .iter()
+            .map(|input| {
+                nn.feed(input);
+                nn.get_output().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn add_neuron_keeps_the_outputs() {
+        let mut rng = TrainingRng::seed_from_u64(5);
+        let mut nn = network(&mut rng);
+        let before = outputs(&mut nn);
+        nn.add_neuron(0, &mut rng);
+        assert_eq!(nn.layers[0].neurons.len(), 6);
+        assert_eq!(outputs(&mut nn), before);
+    }
+
+    #[test]
+    fn add_identity_layer_keeps_the_outputs() {
+        let mut rng = TrainingRng::seed_from_u64(6);
+        for index in 0..2 {
+            let mut nn = network(&mut rng);
+            let before = outputs(&mut nn);
+            nn.add_identity_layer(index);
+            assert_eq!(nn.layers.len(), 3);
+            let after = outputs(&mut nn);
+            for (a, b) in after.iter().flatten().zip(before.iter().flatten()) {
+                assert!((a - b).abs() < 1e-12, "camada {}: {} != {}", index, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn structural_mutation_respects_the_limits() {
+        let config = NeatConfig {
+            add_neuron_rate: 1.0,
+            add_layer_rate: 1.0,
+            remove_neuron_rate: 0.0,
+            remove_layer_rate: 0.0,
+            max_layer_size: 8,
+            max_hidden_layers: 3,
+            ..NeatConfig::default()
+        };
+        let mut rng = TrainingRng::seed_from_u64(7);
+        let mut nn = network(&mut rng);
+        for _ in 0..50 {
+            mutate_structure(&mut nn, &config, &mut rng);
+        }
+        assert_eq!(nn.layers.len() - 1, config.max_hidden_layers);
+        let output = nn.layers.len() - 1;
+        assert!(nn.layers[..output].iter().all(|layer| layer.neurons.len() <= config.max_layer_size));
+        assert_eq!(nn.layers[output].neurons.len(), 3);
+    }
+
+    #[test]
+    fn offspring_add_up_to_the_population() {
+        assert_eq!(allocate_offspring(&[3.0, 1.0], 10), [8, 2]);
+        assert_eq!(allocate_offspring(&[1.0, 1.0, 1.0], 10).iter().sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn breeding_with_a_nan_fitness_keeps_the_population_size() {
+        let mut rng = TrainingRng::seed_from_u64(8);
+        let networks: Vec<NeuralNetwork> = (0..6).map(|_| network(&mut rng)).collect();
+        let fitness = [5.0, 4.0, f64::NAN, 2.0, 1.0, 0.0];
+        let config = TrainingConfig::default();
+        let (children, _) = breed(&networks, &fitness, 6, &config, &[], &mut rng);
+        assert_eq!(children.len(), 6);
+    }
+}
@@ -0,0 +1,577 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use macroquad::math::{vec2, Vec2};
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    controller::{BotController, Controller, Latency, MirroredController},
+    pong::{GameWindow, MatchRule, Pong, Winner},
+};
+use crate::neural_network::network_file::NetworkFileError;
+use crate::neural_network::neural_network_f::{NeuralNetwork, SharedNetwork};
+use crate::training::config::{EvolutionAlgorithm, TrainingConfig};
+use crate::training::fitness::{variance, FitnessAggregation, FitnessConfig, MatchMetrics};
+use crate::training::es::{flat_weights, with_weights, CmaEs, EsState, OpenAiEs};
+use crate::training::league::{match_score, League, OpponentKind};
+use crate::training::neat;
+use crate::training::TrainingRng;
+
+/// Estrutura que mantém um Pong e a Rede Neural associada, além de armazenar a pontuação.
+/// Cada rede joga `episodes` partidas em sequência, cada uma com sua semente.
+pub struct GamePack {
+    pub neural_network: SharedNetwork,
+    pub pong: Pong,
+    pub finished: bool,
+    /// Placar em andamento: rebatidas contra o bot ou o placar da liga (vitórias e margem),
+    /// somado sobre os episódios.
+    pub pontuation: i32,
+    /// Métricas de cada episódio já iniciado; a última é a do episódio em andamento.
+    pub episodes: Vec<MatchMetrics>,
+    /// Aptidão final, calculada por `compute_fitness` ao fim da geração.
+    pub fitness: f64,
+    /// Variância da aptidão entre os episódios.
+    pub fitness_variance: f64,
+    /// Passos de simulação já executados no episódio atual.
+    pub steps: usize,
+    /// Semente do primeiro episódio, guardada para que um checkpoint recrie o mesmo jogo.
+    /// O episódio `i` usa a semente `seed + i`.
+    pub seed: u64,
+    /// Campeões da liga enfrentados, um por episódio. Vazio joga contra o `BotController`.
+    pub opponents: Vec<NeuralNetwork>,
+    /// Quantidade de episódios do jogo.
+    episode_count: usize,
+    /// Regra de cada partida.
+    pub rule: MatchRule,
+    /// Latência das redes em cada partida.
+    pub latency: Latency,
+}
+
+impl GamePack {
+    /// Cria o jogo de treino da rede `nn` na posição `position`: uma partida de um ponto
+    /// contra o `BotController`.
+    pub fn new(nn: SharedNetwork, position: Vec2, seed: u64) -> Self {
+        Self::with_episodes(nn, position, seed, Vec::new(), 1, MatchRule::default(), Latency::default())
+    }
+
+    /// Cria o jogo da rede `nn` com os episódios, a regra e os adversários de `config`.
+    /// `opponents` tem um campeão por episódio, ou é vazio para jogar contra o bot.
+    pub fn for_training(
+        nn: SharedNetwork,
+        position: Vec2,
+        seed: u64,
+        opponents: Vec<NeuralNetwork>,
+        config: &TrainingConfig,
+    ) -> Self {
+        Self::with_episodes(
+            nn,
+            position,
+            seed,
+            opponents,
+            config.episodes,
+            config.match_rule,
+            config.latency,
+        )
+    }
+
+    fn with_episodes(
+        nn: SharedNetwork,
+        position: Vec2,
+        seed: u64,
+        opponents: Vec<NeuralNetwork>,
+        episode_count: usize,
+        rule: MatchRule,
+        latency: Latency,
+    ) -> Self {
+        let pong = Self::new_pong(&nn, position, seed, opponents.first(), rule, latency);
+        GamePack {
+            neural_network: nn,
+            pong,
+            finished: false,
+            pontuation: 0,
+            episodes: vec![MatchMetrics::default()],
+            fitness: 0.0,
+            fitness_variance: 0.0,
+            steps: 0,
+            seed,
+            opponents,
+            episode_count: episode_count.max(1),
+            rule,
+            latency,
+        }
+    }
+
+    fn new_pong(
+        nn: &SharedNetwork,
+        position: Vec2,
+        seed: u64,
+        opponent: Option<&NeuralNetwork>,
+        rule: MatchRule,
+        latency: Latency,
+    ) -> Pong {
+        let window = GameWindow::new(500.0, 400.0);
+        let opponent: Box<dyn Controller> = match opponent {
+            Some(champion) => latency.wrap(MirroredController::new(
+                champion.clone(),
+                position.x + window.width / 2.0,
+            )),
+            None => Box::new(BotController::new()),
+        };
+        let mut pong = Pong::with_seed(window, latency.wrap(nn.clone()), opponent, position, seed);
+        pong.rule = rule;
+        pong
+    }
+
+    /// Contra a liga a aptidão inclui o placar da liga.
+    fn against_league(&self) -> bool {
+        !self.opponents.is_empty()
+    }
+
+    /// Avança o jogo um passo, encerrando o episódio se a partida acabou ou se atingiu
+    /// `max_steps`; o próximo episódio começa em seguida. Retorna `true` se o último
+    /// episódio terminou neste passo.
+    pub fn step(&mut self, max_steps: usize) -> bool {
+        self.steps += 1;
+        let paddle_y = self.pong.player1.position.y;
+        let result = self.pong.step();
+        let metrics = self.episodes.last_mut().unwrap();
+        metrics.travel_distance += (self.pong.player1.position.y - paddle_y).abs() as f64;
+        metrics.steps += 1;
+
+        if result.is_some() || self.steps >= max_steps {
+            self.record_episode(result.map(|stats| stats.winner));
+            let next = self.episodes.len();
+            if next < self.episode_count {
+                let seed = self.seed.wrapping_add(next as u64);
+                let position = self.pong.position;
+                let opponent = self.opponents.get(next);
+                self.pong = Self::new_pong(&self.neural_network, position, seed, opponent, self.rule, self.latency);
+                self.episodes.push(MatchMetrics::default());
+                self.steps = 0;
+            } else {
+                self.finished = true;
+            }
+        }
+        self.pontuation = self.running_score();
+        self.finished
+    }
+
+    /// Guarda nas métricas do episódio atual o placar da partida que acabou de terminar.
+    fn record_episode(&mut self, winner: Option<Winner>) {
+        let metrics = self.episodes.last_mut().unwrap();
+        metrics.hits = self.pong.hits.player1;
+        metrics.opponent_hits = self.pong.hits.player2;
+        metrics.longest_rally = self.pong.longest_rally();
+        metrics.points_won = self.pong.points.player1;
+        metrics.points_conceded = self.pong.points.player2;
+        metrics.wins = (winner == Some(Winner::Player1)) as i32;
+    }
+
+    /// Placar dos episódios encerrados, mais as rebatidas do atual contra o bot.
+    fn running_score(&self) -> i32 {
+        let finished = match self.finished {
+            true => &self.episodes[..],
+            false => &self.episodes[..self.episodes.len() - 1],
+        };
+        if self.against_league() {
+            finished.iter().map(match_score).sum()
+        } else {
+            let current = if self.finished { 0 } else { self.pong.hits.player1 };
+            finished.iter().map(|metrics| metrics.hits).sum::<i32>() + current
+        }
+    }
+
+    /// Calcula e guarda a aptidão do jogo: a de cada episódio vem dos pesos de `weights`
+    /// (mais o placar da liga, contra ela) e os episódios são agregados por `aggregation`.
+    pub fn compute_fitness(&mut self, weights: &FitnessConfig, aggregation: FitnessAggregation) -> f64 {
+        let scores: Vec<f64> = self
+            .episodes
+            .iter()
+            .map(|metrics| {
+                let league_score = match self.against_league() {
+                    true => match_score(metrics) as f64,
+                    false => 0.0,
+                };
+                weights.score(metrics) + league_score
+            })
+            .collect();
+        self.fitness = aggregation.aggregate(&scores);
+        self.fitness_variance = variance(&scores);
+        self.fitness
+    }
+
+    /// Joga a partida até o fim (ou até `max_steps`).
+    pub fn play_to_end(&mut self, max_steps: usize) {
+        while !self.finished {
+            self.step(max_steps);
+        }
+    }
+}
+
+/// Resumo de uma geração, impresso a cada geração pelo treino.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub generation: usize,
+    /// Aptidão média, melhor e pior da geração.
+    pub average: f64,
+    pub best: f64,
+    pub worst: f64,
+    /// Média, entre as redes, da variância da aptidão de cada uma entre seus episódios.
+    pub variance: f64,
+    /// Variância entre os episódios da melhor rede.
+    pub best_variance: f64,
+    /// Quantidade de espécies ao gerar a próxima população; 0 quando não há especiação.
+    pub species: usize,
+    /// Campeões na liga ao fim da geração; 0 quando não há self-play.
+    pub champions: usize,
+}
+
+impl GenerationStats {
+    pub fn from_games(generation: usize, games: &[GamePack]) -> Self {
+        let total: f64 = games.iter().map(|gp| gp.fitness).sum();
+        let count = games.len().max(1) as f64;
+        let best = games.iter().max_by(|a, b| a.fitness.total_cmp(&b.fitness));
+        GenerationStats {
+            generation,
+            average: total / count,
+            best: best.map_or(0.0, |gp| gp.fitness),
+            worst: games.iter().map(|gp| gp.fitness).reduce(f64::min).unwrap_or(0.0),
+            variance: games.iter().map(|gp| gp.fitness_variance).sum::<f64>() / count,
+            best_variance: best.map_or(0.0, |gp| gp.fitness_variance),
+            species: 0,
+            champions: 0,
+        }
+    }
+}
+
+/// Estado completo de um treino: população atual, geração, histórico e gerador.
+/// É exatamente o que um checkpoint precisa guardar para retomar o treino.
+pub struct TrainingState {
+    pub games: Vec<GamePack>,
+    /// Quantidade de gerações já concluídas.
+    pub generation: usize,
+    pub history: Vec<GenerationStats>,
+    pub rng: TrainingRng,
+    /// Distribuição da estratégia evolutiva, nos algoritmos `Es` e `CmaEs`.
+    /// É criada ao fim da primeira geração, centrada na melhor rede.
+    pub strategy: Option<EsState>,
+    /// Campeões usados como adversários com `opponent: League`.
+    pub league: League,
+}
+
+impl TrainingState {
+    /// Começa um treino do zero com a semente `seed`.
+    pub fn new(config: &TrainingConfig, seed: u64) -> Result<Self, NetworkFileError> {
+        let mut rng = TrainingRng::seed_from_u64(seed);
+        // A rede inicial é o primeiro campeão da liga
+        let mut league = League::default();
+        if config.opponent == OpponentKind::League {
+            if let Some(path) = &config.initial_network {
+                league.champions.push(NeuralNetwork::load_neural_network(path)?);
+            }
+        }
+        let games = create_initial_games(config, &league, &mut rng)?;
+        Ok(TrainingState {
+            games,
+            generation: 0,
+            history: Vec::new(),
+            rng,
+            strategy: None,
+            league,
+        })
+    }
+
+    /// Encerra a geração atual (cujos jogos já foram jogados): registra as estatísticas,
+    /// gera as novas redes e reinstancia os jogos com elas.
+    pub fn finish_generation(&mut self, config: &TrainingConfig) -> GenerationStats {
+        self.generation += 1;
+        for game in self.games.iter_mut() {
+            game.compute_fitness(&config.fitness, config.fitness_aggregation);
+        }
+        self.history
+            .push(GenerationStats::from_games(self.generation, &self.games));
+        // Enquanto a liga está vazia a melhor rede entra já na primeira geração
+        let promote = self.league.is_empty() || self.generation.is_multiple_of(config.league.promote_every);
+        if config.opponent == OpponentKind::League && promote {
+            let best = self.games.iter().max_by(|a, b| a.fitness.total_cmp(&b.fitness)).unwrap();
+            let champion = best.neural_network.lock().unwrap().clone();
+            self.league.promote(&champion, best.pontuation, &config.league);
+        }
+        let (new_nns, species) = match config.algorithm {
+            EvolutionAlgorithm::Es | EvolutionAlgorithm::CmaEs => {
+                let new_nns = generate_es(&mut self.games, &mut self.strategy, config, &mut self.rng);
+                (new_nns, 0)
+            }
+            _ => generate_nn(&mut self.games, config, &self.history, &mut self.rng),
+        };
+        regenerate_generation(&mut self.games, &new_nns, config, &self.league, &mut self.rng);
+        self.games.sort_by(|a, b| a.pong.position.y.partial_cmp(&b.pong.position.y).unwrap());
+
+        let stats = self.history.last_mut().unwrap();
+        stats.species = species;
+        stats.champions = self.league.champions.len();
+        *stats
+    }
+
+    /// Roda uma geração inteira sem janela, o mais rápido que a CPU permitir, e então
+    /// a substitui pela próxima. Cada jogo vai até o fim (ou até `max_steps`), em paralelo
+    /// em `config.threads` threads. Retorna as estatísticas da geração avaliada.
+    pub fn run_generation_headless(&mut self, config: &TrainingConfig) -> GenerationStats {
+        play_all_games(&mut self.games, config.max_steps, config.threads);
+        self.finish_generation(config)
+    }
+}
+
+/// Cria o conjunto inicial de jogos (GamePack).
+/// Se `initial_network` estiver configurada, todos começam com cópias dela;
+/// caso contrário cada jogo recebe uma rede aleatória com a topologia configurada.
+pub fn create_initial_games(
+    config: &TrainingConfig,
+    league: &League,
+    rng: &mut TrainingRng,
+) -> Result<Vec<GamePack>, NetworkFileError> {
+    let initial_network = match &config.initial_network {
+        Some(path) => Some(NeuralNetwork::load_neural_network(path)?),
+        None => None,
+    };
+
+    let mut games: Vec<GamePack> = Vec::new();
+    let mut game_x = 0.0;
+    let mut game_y = 0.0;
+
+    for i in 0..config.games {
+        // A cada nova linha, reinicia a posição horizontal e avança a vertical
+        if i % config.games_line == 0 {
+            game_x = 0.0;
+            game_y += 500.0;
+        }
+
+        let nn = Arc::new(Mutex::new(match &initial_network {
+            Some(nn) => nn.clone(),
+            None => config
+                .topology
+                .generate_with_rng(rng)
+                .with_observation(config.observation.clone()),
+        }));
+
+        let seed = rng.random();
+        let opponents = sample_opponents(config, league, rng);
+        games.push(GamePack::for_training(nn, vec2(game_x, game_y), seed, opponents, config));
+
+        // Espaçamento horizontal entre os jogos
+        game_x += 600.0;
+    }
+    Ok(games)
+}
+
+/// Atualiza e conta quantos jogos estão finalizados. Também determina qual é o melhor jogo.
+/// Um jogo que chega a `max_steps` passos é encerrado com a pontuação que tiver.
+pub fn update_all_games(games: &mut [GamePack], best_game_index: &mut usize, max_steps: usize) -> usize {
+    let mut finished_count = 0;
+    let mut best_score = 0;
+
+    for (i, game) in games.iter_mut().enumerate() {
+        if game.finished || game.step(max_steps) {
+            finished_count += 1;
+            continue;
+        }
+
+        if game.pontuation > best_score {
+            best_score = game.pontuation;
+            *best_game_index = i;
+        }
+    }
+    finished_count
+}
+
+/// Quantidade de threads a usar: `threads` se for maior que zero, senão todos os núcleos.
+pub fn thread_count(threads: usize) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+}
+
+/// Joga todas as partidas até o fim, dividindo os jogos entre `threads` threads.
+/// Cada jogo tem seu próprio gerador, então o resultado é o mesmo de uma execução sequencial.
+pub fn play_all_games(games: &mut [GamePack], max_steps: usize, threads: usize) {
+    let chunk_size = games.len().div_ceil(thread_count(threads).max(1)).max(1);
+    thread::scope(|scope| {
+        for chunk in games.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                for game in chunk {
+                    game.play_to_end(max_steps);
+                }
+            });
+        }
+    });
+}
+
+/// Gera nova geração de redes neurais e reinstancia cada jogo com a nova RNA, mantendo a mesma posição.
+pub fn regenerate_generation(
+    games: &mut [GamePack],
+    new_nns: &[SharedNetwork],
+    config: &TrainingConfig,
+    league: &League,
+    rng: &mut TrainingRng,
+) {
+    for (i, game_pack) in games.iter_mut().enumerate() {
+        let pos = game_pack.pong.position;
+        let seed = rng.random();
+        let opponents = sample_opponents(config, league, rng);
+        *game_pack = GamePack::for_training(new_nns[i].clone(), pos, seed, opponents, config);
+    }
+}
+
+/// Adversários de um jogo: campeões sorteados da liga com `opponent: League`, ou nenhum
+/// (o `BotController`) contra o bot ou enquanto a liga está vazia.
+fn sample_opponents(config: &TrainingConfig, league: &League, rng: &mut TrainingRng) -> Vec<NeuralNetwork> {
+    match config.opponent {
+        OpponentKind::Bot => Vec::new(),
+        OpponentKind::League => league.sample(config.episodes, rng),
+    }
+}
+
+/// Gera novas redes neurais com base na população anterior.
+/// `history` inclui a geração que acabou de ser avaliada. Retorna as redes e a quantidade
+/// de espécies formadas (0 fora do modo NEAT).
+pub fn generate_nn(
+    game_packs: &mut [GamePack],
+    config: &TrainingConfig,
+    history: &[GenerationStats],
+    rng: &mut TrainingRng,
+) -> (Vec<SharedNetwork>, usize) {
+    game_packs.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    let fitness: Vec<f64> = game_packs.iter().map(|gp| gp.fitness).collect();
+
+    // Salva a melhor rede neural
+    if let Err(e) = game_packs[0].neural_network.lock().unwrap().save_neural_network(&config.output) {
+        eprintln!("Falha ao salvar {}: {}", config.output, e);
+    }
+
+    // A elite passa intacta
+    let mut new_nns = Vec::with_capacity(config.games);
+    for gp in game_packs.iter().take(config.elite_count()) {
+        new_nns.push(deep_clone_nn(&gp.neural_network));
+    }
+
+    if config.algorithm == EvolutionAlgorithm::Neat {
+        let networks: Vec<NeuralNetwork> = game_packs
+            .iter()
+            .map(|gp| gp.neural_network.lock().unwrap().clone())
+            .collect();
+        let count = config.games - new_nns.len();
+        let (children, species) = neat::breed(&networks, &fitness, count, config, history, rng);
+        new_nns.extend(children.into_iter().map(|nn| Arc::new(Mutex::new(nn))));
+        return (new_nns, species);
+    }
+
+    let selection = config.selection.build(config);
+    let mutation = config.mutation.build(config, history);
+    while new_nns.len() < config.games {
+        let chosen = &game_packs[selection.select(&fitness, rng)].neural_network;
+        let mut nn = chosen.lock().unwrap().clone();
+
+        // Cruza com um segundo pai
+        if config.crossover_rate > 0.0 && rng.random_bool(config.crossover_rate) {
+            let other = &game_packs[selection.select(&fitness, rng)].neural_network;
+            let other = other.lock().unwrap().clone();
+            nn = config.crossover.crossover(&nn, &other, rng);
+        }
+
+        mutation.mutate(&mut nn, rng);
+        new_nns.push(Arc::new(Mutex::new(nn)));
+    }
+
+    (new_nns, 0)
+}
+
+/// Gera a próxima população com uma estratégia evolutiva: informa à distribuição as
+/// pontuações da geração jogada e amostra dela as novas redes. Na primeira chamada a
+/// distribuição é criada com média nos pesos da melhor rede.
+pub fn generate_es(
+    game_packs: &mut [GamePack],
+    strategy: &mut Option<EsState>,
+    config: &TrainingConfig,
+    rng: &mut TrainingRng,
+) -> Vec<SharedNetwork> {
+    game_packs.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    let fitness: Vec<f64> = game_packs.iter().map(|gp| gp.fitness).collect();
+    let mut networks: Vec<NeuralNetwork> = game_packs
+        .iter()
+        .map(|gp| gp.neural_network.lock().unwrap().clone())
+        .collect();
+
+    // Salva a melhor rede neural
+    if let Err(e) = networks[0].save_neural_network(&config.output) {
+        eprintln!("Falha ao salvar {}: {}", config.output, e);
+    }
+
+    let strategy = match strategy {
+        Some(state) => {
+            let samples: Vec<Vec<f64>> = networks.iter_mut().map(flat_weights).collect();
+            state.strategy().tell(&samples, &fitness);
+            state
+        }
+        None => {
+            let mean = flat_weights(&mut networks[0]);
+            strategy.insert(match config.algorithm {
+                EvolutionAlgorithm::CmaEs => EsState::Cma(CmaEs::new(mean, &config.es)),
+                _ => EsState::OpenAi(OpenAiEs::new(mean, &config.es)),
+            })
+        }
+    };
+
+    let template = &networks[0];
+    strategy
+        .strategy()
+        .ask(config.games, rng)
+        .iter()
+        .map(|weights| Arc::new(Mutex::new(with_weights(template, weights))))
+        .collect()
+}
+
+/// Indica se a geração atual já pode ser substituída: quase todos os jogos terminaram,
+/// sobrando no máximo a quantidade da elite ainda em andamento.
+pub fn generation_finished(finished_count: usize, config: &TrainingConfig) -> bool {
+    finished_count >= config.games - config.elite_count()
+}
+
+/// Realiza deep clone de uma rede compartilhada
+fn deep_clone_nn(original: &SharedNetwork) -> SharedNetwork {
+    let copy_of_nn = original.lock().unwrap().clone();
+    Arc::new(Mutex::new(copy_of_nn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fitness_with_threads(threads: usize) -> Vec<f64> {
+        let config = TrainingConfig {
+            games: 12,
+            episodes: 2,
+            max_steps: 1200,
+            initial_network: None,
+            ..TrainingConfig::default()
+        };
+        let mut state = TrainingState::new(&config, 7).unwrap();
+        play_all_games(&mut state.games, config.max_steps, threads);
+        state
+            .games
+            .iter_mut()
+            .map(|game| {
+                game.compute_fitness(&config.fitness, config.fitness_aggregation);
+                game.fitness
+            })
+            .collect()
+    }
+
+    #[test]
+    fn thread_count_does_not_change_fitness() {
+        let sequential = fitness_with_threads(1);
+        assert_eq!(sequential, fitness_with_threads(4));
+        assert!(sequential.iter().any(|&fitness| fitness > 0.0));
+    }
+}
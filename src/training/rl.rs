@@ -0,0 +1,425 @@
+//! Aprendizado por reforço sobre o `PongEnv`: REINFORCE (gradiente de política), aqui, ou
+//! DQN com buffer de replay (ver `training::dqn`), escolhido por `RlConfig::algorithm`.
+//!
+//! No REINFORCE a política é a própria rede do controlador: as saídas 0 e 1 são as
+//! probabilidades de apertar para cima e para baixo, sorteadas de forma independente durante
+//! o treino (as duas juntas param o jogador, como no `Controller` da rede). A saída 2, a
+//! velocidade, é usada como está e não é treinada. No jogo a rede decide pelo limiar de 0,5,
+//! ou seja, joga a ação mais provável da política aprendida.
+
+use std::error::Error;
+use std::str::FromStr;
+
+use rand::{Rng, SeedableRng};
+
+use crate::cli::Args;
+use crate::game::controller::PlayerDirection;
+use crate::game::env::{Environment, PongEnv};
+use crate::game::observation::{ObservationConfig, ObservationFeature};
+use crate::neural_network::neural_network_f::{NeuralNetwork, NeuralNetworkModel, CONTROLLER_OUTPUTS};
+use crate::neural_network::neuron::ActivationFunction;
+use crate::neural_network::optimizer::{Loss, NetworkGradients, OptimizerKind};
+use crate::training::dqn::{self, DqnConfig};
+use crate::training::TrainingRng;
+
+/// Algoritmo do treino por reforço.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RlAlgorithm {
+    /// Gradiente de política, atualizado ao fim de cada lote de episódios.
+    Reinforce,
+    /// Q-learning com buffer de replay e rede alvo.
+    Dqn,
+}
+
+impl FromStr for RlAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "reinforce" => Ok(RlAlgorithm::Reinforce),
+            "dqn" => Ok(RlAlgorithm::Dqn),
+            _ => Err(format!("algoritmo de reforço desconhecido: {} (use reinforce ou dqn)", s)),
+        }
+    }
+}
+
+/// Parâmetros do treino por reforço.
+#[derive(Debug, Clone)]
+pub struct RlConfig {
+    pub algorithm: RlAlgorithm,
+    /// Total de episódios (pontos) jogados.
+    pub episodes: usize,
+    /// Episódios jogados antes de cada atualização da rede.
+    pub batch_episodes: usize,
+    /// Desconto das recompensas futuras por passo.
+    pub gamma: f64,
+    pub learning_rate: f64,
+    /// Momento do SGD; ignorado pelo Adam.
+    pub momentum: f64,
+    pub optimizer: OptimizerKind,
+    /// Limite de passos por episódio.
+    pub max_steps: usize,
+    /// Topologia da rede treinada do zero.
+    pub topology: NeuralNetworkModel,
+    /// Features da rede treinada do zero; uma rede inicial usa as suas.
+    pub observation: ObservationConfig,
+    /// Rede de partida. `None` começa de uma rede aleatória com `topology`.
+    pub initial_network: Option<String>,
+    /// Semente dos pesos iniciais, das ações sorteadas e das partidas.
+    pub seed: u64,
+    /// Parâmetros usados com `algorithm: Dqn`.
+    pub dqn: DqnConfig,
+}
+
+impl Default for RlConfig {
+    fn default() -> Self {
+        let observation = ObservationConfig::default();
+        RlConfig {
+            algorithm: RlAlgorithm::Reinforce,
+            episodes: 2000,
+            batch_episodes: 10,
+            gamma: 0.99,
+            learning_rate: 0.005,
+            momentum: 0.9,
+            optimizer: OptimizerKind::Adam,
+            max_steps: 7200,
+            topology: NeuralNetworkModel::new(
+                observation.size(),
+                vec![5, CONTROLLER_OUTPUTS],
+                vec![ActivationFunction::Relu, ActivationFunction::Sigmoid],
+            ),
+            observation,
+            initial_network: None,
+            seed: 0,
+            dqn: DqnConfig::default(),
+        }
+    }
+}
+
+impl RlConfig {
+    /// Monta a configuração a partir das flags da linha de comando.
+    pub fn from_args(args: &Args) -> Result<Self, String> {
+        let mut config = RlConfig::default();
+        // O DQN tem outra topologia padrão; `--layers` e `--activations` valem por cima dela
+        if let Some(algorithm) = args.parse_value("algorithm")? {
+            config.algorithm = algorithm;
+            if algorithm == RlAlgorithm::Dqn {
+                config.topology = dqn::default_topology(config.observation.size());
+            }
+        }
+        if let Some(episodes) = args.parse_value("episodes")? {
+            config.episodes = episodes;
+        }
+        if let Some(batch_episodes) = args.parse_value("batch-episodes")? {
+            config.batch_episodes = batch_episodes;
+        }
+        if let Some(gamma) = args.parse_value("gamma")? {
+            config.gamma = gamma;
+        }
+        if let Some(learning_rate) = args.parse_value("learning-rate")? {
+            config.learning_rate = learning_rate;
+        }
+        if let Some(momentum) = args.parse_value("momentum")? {
+            config.momentum = momentum;
+        }
+        if let Some(optimizer) = args.parse_value("optimizer")? {
+            config.optimizer = optimizer;
+        }
+        if let Some(max_steps) = args.parse_value("max-steps")? {
+            config.max_steps = max_steps;
+        }
+        if let Some(layers) = args.parse_list("layers")? {
+            config.topology.hidden_layers_sizes = layers;
+        }
+        if let Some(activations) = args.parse_list::<ActivationFunction>("activations")? {
+            config.topology.activation_functions = activations;
+        }
+        if let Some(features) = args.parse_list::<ObservationFeature>("features")? {
+            config.observation.features = features;
+            config.topology.input_layer_size = config.observation.size();
+        }
+        if args.flag("normalize") {
+            config.observation.normalize = true;
+        }
+        if let Some(replay_capacity) = args.parse_value("replay-capacity")? {
+            config.dqn.replay_capacity = replay_capacity;
+        }
+        if let Some(replay_batch) = args.parse_value("replay-batch")? {
+            config.dqn.replay_batch = replay_batch;
+        }
+        if let Some(learning_starts) = args.parse_value("learning-starts")? {
+            config.dqn.learning_starts = learning_starts;
+        }
+        if let Some(target_update) = args.parse_value("target-update")? {
+            config.dqn.target_update = target_update;
+        }
+        if let Some(epsilon_start) = args.parse_value("epsilon-start")? {
+            config.dqn.epsilon_start = epsilon_start;
+        }
+        if let Some(epsilon_end) = args.parse_value("epsilon-end")? {
+            config.dqn.epsilon_end = epsilon_end;
+        }
+        if let Some(epsilon_decay_episodes) = args.parse_value("epsilon-decay")? {
+            config.dqn.epsilon_decay_episodes = epsilon_decay_episodes;
+        }
+        config.initial_network = args.parse_value("initial-network")?;
+        // A rede inicial joga com as features gravadas nela; as flags seriam ignoradas
+        if (args.flag("features") || args.flag("normalize")) && config.initial_network.is_some() {
+            return Err("--features e --normalize só valem para redes geradas do zero, sem --initial-network".into());
+        }
+        if let Some(seed) = args.parse_value("seed")? {
+            config.seed = seed;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.episodes == 0 || self.batch_episodes == 0 || self.max_steps == 0 {
+            return Err("episodes, batch_episodes e max_steps devem ser maiores que zero".into());
+        }
+        if !(0.0..=1.0).contains(&self.gamma) {
+            return Err("gamma deve estar entre 0 e 1".into());
+        }
+        if self.learning_rate <= 0.0 {
+            return Err("learning_rate deve ser positiva".into());
+        }
+        if self.topology.hidden_layers_sizes.len() != self.topology.activation_functions.len() {
+            return Err("topology: layers e activations devem ter o mesmo tamanho".into());
+        }
+        if self.topology.hidden_layers_sizes.last() != Some(&CONTROLLER_OUTPUTS) {
+            return Err(format!("a última camada deve ter {} neurônios", CONTROLLER_OUTPUTS));
+        }
+        self.dqn.validate()?;
+        Ok(())
+    }
+}
+
+/// Um passo jogado: a observação, as teclas sorteadas e a recompensa recebida.
+struct Step {
+    observation: Vec<f64>,
+    up: bool,
+    down: bool,
+    speed: f64,
+    reward: f64,
+}
+
+/// Resumo de uma atualização da rede.
+#[derive(Debug, Clone, Copy)]
+pub struct RlStats {
+    /// Episódios jogados até aqui.
+    pub episodes: usize,
+    /// Recompensa média por episódio do lote.
+    pub average_reward: f64,
+    /// Rebatidas médias por episódio do lote.
+    pub average_hits: f64,
+}
+
+/// Probabilidade de apertar uma tecla dada a saída da rede. Uma saída não finita (rede
+/// divergindo) vira 0,5 em vez de derrubar o sorteio.
+fn press_probability(output: f64) -> f64 {
+    if output.is_finite() {
+        output.clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+/// Joga um episódio sorteando as ações da política de `nn`.
+fn play_episode(nn: &mut NeuralNetwork, env: &mut PongEnv, rng: &mut TrainingRng) -> (Vec<Step>, i32) {
+    let mut observation = env.reset(rng.random());
+    let mut steps = Vec::new();
+    loop {
+        nn.feed(&observation);
+        let output = nn.get_output().unwrap();
+        let up = rng.random_bool(press_probability(output[0]));
+        let down = rng.random_bool(press_probability(output[1]));
+        let direction = match (up, down) {
+            (true, false) => PlayerDirection::Up,
+            (false, true) => PlayerDirection::Down,
+            _ => PlayerDirection::None,
+        };
+
+        let (next, reward, done, info) = env.step((direction, output[2]));
+        steps.push(Step {
+            observation,
+            up,
+            down,
+            speed: output[2],
+            reward,
+        });
+        if done {
+            return (steps, info.hits);
+        }
+        observation = next;
+    }
+}
+
+/// Retornos descontados de cada passo, normalizados para média 0 e desvio 1
+/// (a média faz o papel de baseline e reduz a variância do gradiente).
+fn normalized_returns(rewards: &[f64], gamma: f64) -> Vec<f64> {
+    let mut returns = vec![0.0; rewards.len()];
+    let mut running = 0.0;
+    for (i, reward) in rewards.iter().enumerate().rev() {
+        running = reward + gamma * running;
+        returns[i] = running;
+    }
+
+    let n = returns.len().max(1) as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if std > 1e-8 {
+        returns.iter_mut().for_each(|r| *r = (*r - mean) / std);
+    } else {
+        returns.iter_mut().for_each(|r| *r -= mean);
+    }
+    returns
+}
+
+/// Soma a `gradients` o gradiente de REINFORCE dos passos de um episódio. `scratch` guarda
+/// o gradiente de cada passo e só existe para não ser alocado de novo a cada chamada.
+fn add_episode_gradients(
+    nn: &mut NeuralNetwork,
+    steps: &[Step],
+    gamma: f64,
+    gradients: &mut NetworkGradients,
+    scratch: &mut NetworkGradients,
+) {
+    let rewards: Vec<f64> = steps.iter().map(|step| step.reward).collect();
+    // A entropia cruzada com a ação sorteada como alvo é -log π(ação); descer
+    // o gradiente dela ponderado pelo retorno sobe a probabilidade das ações boas.
+    // A velocidade tem como alvo a própria saída, então não recebe gradiente.
+    for (step, advantage) in steps.iter().zip(normalized_returns(&rewards, gamma)) {
+        scratch.scale(0.0);
+        let targets = [step.up as u8 as f64, step.down as u8 as f64, step.speed];
+        nn.backward(&step.observation, &targets, Loss::BinaryCrossEntropy, scratch);
+        gradients.add_scaled(scratch, advantage);
+    }
+}
+
+/// Treina uma política com o algoritmo de `config`. `on_update` recebe o resumo de cada lote
+/// de episódios.
+pub fn train_policy(config: &RlConfig, on_update: impl FnMut(RlStats)) -> Result<NeuralNetwork, Box<dyn Error>> {
+    match config.algorithm {
+        RlAlgorithm::Reinforce => train_reinforce(config, on_update),
+        RlAlgorithm::Dqn => dqn::train_q_network(config, on_update),
+    }
+}
+
+/// Treina uma política por REINFORCE.
+fn train_reinforce(
+    config: &RlConfig,
+    mut on_update: impl FnMut(RlStats),
+) -> Result<NeuralNetwork, Box<dyn Error>> {
+    let mut rng = TrainingRng::seed_from_u64(config.seed);
+    let mut nn = match &config.initial_network {
+        Some(path) => NeuralNetwork::load_neural_network(path)?,
+        None => config
+            .topology
+            .generate_with_rng(&mut rng)
+            .with_observation(config.observation.clone()),
+    };
+    let output_size = nn.layers.last().map_or(0, |layer| layer.neurons.len());
+    if nn.input_size() != nn.observation.size() || output_size != CONTROLLER_OUTPUTS {
+        return Err(format!(
+            "a rede precisa ter {} inputs e {} saídas, tem {} e {}",
+            nn.observation.size(),
+            CONTROLLER_OUTPUTS,
+            nn.input_size(),
+            output_size
+        )
+        .into());
+    }
+
+    let mut env = PongEnv::new(nn.observation.clone(), config.max_steps);
+    let mut optimizer = config.optimizer.build(config.learning_rate, config.momentum);
+    let mut played = 0;
+    while played < config.episodes {
+        let batch = config.batch_episodes.min(config.episodes - played);
+        let mut gradients = NetworkGradients::zeros_like(&nn);
+        let mut step_gradients = NetworkGradients::zeros_like(&nn);
+        let mut total_reward = 0.0;
+        let mut total_hits = 0;
+        let mut total_steps = 0;
+
+        for _ in 0..batch {
+            let (steps, hits) = play_episode(&mut nn, &mut env, &mut rng);
+            total_reward += steps.iter().map(|step| step.reward).sum::<f64>();
+            total_hits += hits;
+            total_steps += steps.len();
+            add_episode_gradients(&mut nn, &steps, config.gamma, &mut gradients, &mut step_gradients);
+        }
+
+        gradients.scale(1.0 / total_steps.max(1) as f64);
+        optimizer.step(&mut nn, &gradients);
+        played += batch;
+        on_update(RlStats {
+            episodes: played,
+            average_reward: total_reward / batch as f64,
+            average_hits: total_hits as f64 / batch as f64,
+        });
+    }
+    Ok(nn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_returns_have_zero_mean_and_unit_std() {
+        let returns = normalized_returns(&[0.0, 1.0, 0.0, 0.0, -1.0, 1.0], 0.9);
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n).sqrt();
+        assert!(mean.abs() < 1e-12, "{}", mean);
+        assert!((std - 1.0).abs() < 1e-12, "{}", std);
+
+        // Retornos iguais não têm desvio: só a média é tirada
+        assert_eq!(normalized_returns(&[0.0, 0.0], 0.5), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn an_update_raises_the_probability_of_the_rewarded_action() {
+        let mut rng = TrainingRng::seed_from_u64(4);
+        let mut nn = RlConfig::default().topology.generate_with_rng(&mut rng);
+        let observation = vec![0.4, 0.2, -0.3, 0.1];
+        let log_up = |nn: &mut NeuralNetwork| {
+            nn.feed(&observation);
+            nn.get_output().unwrap()[0].ln()
+        };
+        let before = log_up(&mut nn);
+
+        // Subir foi recompensado e não subir, na mesma situação, não foi
+        let step = |up: bool, reward: f64| Step {
+            observation: observation.clone(),
+            up,
+            down: false,
+            speed: 0.5,
+            reward,
+        };
+        let steps = [step(true, 1.0), step(false, 0.0)];
+        let mut gradients = NetworkGradients::zeros_like(&nn);
+        let mut scratch = NetworkGradients::zeros_like(&nn);
+        add_episode_gradients(&mut nn, &steps, 0.0, &mut gradients, &mut scratch);
+        OptimizerKind::Sgd.build(0.01, 0.0).step(&mut nn, &gradients);
+
+        let after = log_up(&mut nn);
+        assert!(after > before, "{} -> {}", before, after);
+    }
+
+    #[test]
+    fn non_finite_outputs_press_with_even_odds() {
+        assert_eq!(press_probability(f64::NAN), 0.5);
+        assert_eq!(press_probability(f64::INFINITY), 0.5);
+        assert_eq!(press_probability(1.7), 1.0);
+        assert_eq!(press_probability(0.25), 0.25);
+
+        let mut rng = TrainingRng::seed_from_u64(5);
+        let mut nn = RlConfig::default().topology.generate_with_rng(&mut rng);
+        nn.layers[1].neurons[0].bias = f64::NAN;
+        let mut env = PongEnv::new(ObservationConfig::default(), 50);
+        let (steps, _) = play_episode(&mut nn, &mut env, &mut rng);
+        assert!(!steps.is_empty());
+    }
+}
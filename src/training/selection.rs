@@ -0,0 +1,188 @@
+//! Estratégias de seleção dos pais de cada nova rede.
+//!
+//! Todas recebem a aptidão da população já ordenada da melhor para a pior e devolvem
+//! o índice do pai sorteado. A elite que passa intacta para a próxima geração é
+//! configurada à parte (`TrainingConfig::elite_fraction`).
+
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::training::config::TrainingConfig;
+use crate::training::TrainingRng;
+
+/// Escolhe um pai a partir da aptidão da população.
+pub trait SelectionStrategy {
+    /// `fitness` está ordenada da maior para a menor e não é vazia.
+    fn select(&self, fitness: &[f64], rng: &mut TrainingRng) -> usize;
+}
+
+/// Estratégias disponíveis na configuração.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionKind {
+    Tournament,
+    Roulette,
+    Rank,
+    Truncation,
+}
+
+impl SelectionKind {
+    /// Cria a estratégia com os parâmetros de `config`.
+    pub fn build(self, config: &TrainingConfig) -> Box<dyn SelectionStrategy> {
+        match self {
+            SelectionKind::Tournament => Box::new(Tournament {
+                size: config.tournament_size,
+            }),
+            SelectionKind::Roulette => Box::new(Roulette),
+            SelectionKind::Rank => Box::new(Rank),
+            SelectionKind::Truncation => Box::new(Truncation {
+                fraction: config.selection_fraction,
+            }),
+        }
+    }
+}
+
+impl FromStr for SelectionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tournament" => Ok(SelectionKind::Tournament),
+            "roulette" => Ok(SelectionKind::Roulette),
+            "rank" => Ok(SelectionKind::Rank),
+            "truncation" => Ok(SelectionKind::Truncation),
+            _ => Err(format!(
+                "seleção desconhecida: {} (use tournament, roulette, rank ou truncation)",
+                s
+            )),
+        }
+    }
+}
+
+/// Sorteia `size` indivíduos e fica com o melhor deles.
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl SelectionStrategy for Tournament {
+    fn select(&self, fitness: &[f64], rng: &mut TrainingRng) -> usize {
+        // Como `fitness` está ordenada, o melhor do torneio é o de menor índice
+        (0..self.size.max(1))
+            .map(|_| rng.random_range(0..fitness.len()))
+            .min()
+            .unwrap()
+    }
+}
+
+/// Probabilidade proporcional à aptidão (roleta). As aptidões são deslocadas para
+/// que a pior valha zero; se todas forem iguais a escolha é uniforme.
+pub struct Roulette;
+
+impl SelectionStrategy for Roulette {
+    fn select(&self, fitness: &[f64], rng: &mut TrainingRng) -> usize {
+        let worst = fitness[fitness.len() - 1];
+        let total: f64 = fitness.iter().map(|f| f - worst).sum();
+        if total <= 0.0 {
+            return rng.random_range(0..fitness.len());
+        }
+        weighted_index(fitness.iter().map(|f| f - worst), total, rng)
+    }
+}
+
+/// Probabilidade proporcional à posição no ranking: o melhor de `n` tem peso `n`, o pior peso 1.
+pub struct Rank;
+
+impl SelectionStrategy for Rank {
+    fn select(&self, fitness: &[f64], rng: &mut TrainingRng) -> usize {
+        let n = fitness.len();
+        let total = (n * (n + 1) / 2) as f64;
+        weighted_index((0..n).map(|i| (n - i) as f64), total, rng)
+    }
+}
+
+/// Escolha uniforme entre a fração `fraction` melhor da população.
+pub struct Truncation {
+    pub fraction: f64,
+}
+
+impl SelectionStrategy for Truncation {
+    fn select(&self, fitness: &[f64], rng: &mut TrainingRng) -> usize {
+        let pool = ((self.fraction * fitness.len() as f64).ceil() as usize).clamp(1, fitness.len());
+        rng.random_range(0..pool)
+    }
+}
+
+/// Sorteia um índice com probabilidade proporcional a `weights` (que somam `total`).
+fn weighted_index(weights: impl Iterator<Item = f64>, total: f64, rng: &mut TrainingRng) -> usize {
+    let mut target = rng.random_range(0.0..total);
+    let mut last = 0;
+    for (i, weight) in weights.enumerate() {
+        if target < weight {
+            return i;
+        }
+        target -= weight;
+        last = i;
+    }
+    // Arredondamentos podem deixar sobrar um resto mínimo
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    /// Quantas vezes cada índice é escolhido em `draws` seleções.
+    fn counts(strategy: &dyn SelectionStrategy, fitness: &[f64], draws: usize) -> Vec<usize> {
+        let mut rng = TrainingRng::seed_from_u64(1);
+        let mut counts = vec![0; fitness.len()];
+        for _ in 0..draws {
+            counts[strategy.select(fitness, &mut rng)] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn tournament_prefers_the_fitter() {
+        let fitness = [9.0, 5.0, 2.0, 1.0];
+        let picks = counts(&Tournament { size: 3 }, &fitness, 4000);
+        assert!(picks.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", picks);
+    }
+
+    #[test]
+    fn roulette_follows_the_shifted_fitness() {
+        // Deslocadas pela pior: pesos 3, 1 e 0
+        let fitness = [4.0, 2.0, 1.0];
+        let picks = counts(&Roulette, &fitness, 4000);
+        assert_eq!(picks[2], 0);
+        let share = picks[0] as f64 / 4000.0;
+        assert!((share - 0.75).abs() < 0.03, "{:?}", picks);
+    }
+
+    #[test]
+    fn roulette_with_equal_fitness_is_uniform() {
+        let picks = counts(&Roulette, &[1.0; 4], 4000);
+        assert!(picks.iter().all(|&count| count > 800), "{:?}", picks);
+    }
+
+    #[test]
+    fn truncation_only_picks_the_best_fraction() {
+        let fitness = [5.0, 4.0, 3.0, 2.0, 1.0, 0.0];
+        let picks = counts(&Truncation { fraction: 0.34 }, &fitness, 1000);
+        assert!(picks[..3].iter().all(|&count| count > 0));
+        assert!(picks[3..].iter().all(|&count| count == 0), "{:?}", picks);
+    }
+
+    #[test]
+    fn rank_prefers_the_higher_ranked() {
+        // Só a ordem importa: pesos 4, 3, 2 e 1, mesmo com aptidões negativas
+        let fitness = [-1.0, -2.0, -50.0, -1000.0];
+        let picks = counts(&Rank, &fitness, 10_000);
+        assert!(picks.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", picks);
+        let share = picks[0] as f64 / 10_000.0;
+        assert!((share - 0.4).abs() < 0.03, "{:?}", picks);
+        assert!(picks[3] > 0);
+    }
+}